@@ -69,17 +69,41 @@ pub fn add_two(x: i32) -> i32 {
     x + 2 // change to x + 3 to see custom assertion error message
 }
 
-struct Guess {
+pub struct Guess {
     value: i32,
 }
 
+// The try_ constructors report a bad value as an Err, for callers that want to
+// recover from it; new and new_in_range panic with the same message instead
 impl Guess {
     pub fn new(value: i32) -> Guess {
-        if value < 1 || value > 100 {
-            panic!("Guess value must be between 1 and 100, got {}.", value);
+        Guess::new_in_range(value, 1, 100)
+    }
+
+    // Same check as new, but against any bounds (both inclusive)
+    pub fn new_in_range(value: i32, min: i32, max: i32) -> Guess {
+        Guess::try_new_in_range(value, min, max).unwrap_or_else(|msg| panic!("{}", msg))
+    }
+
+    pub fn try_new(value: i32) -> Result<Guess, String> {
+        Guess::try_new_in_range(value, 1, 100)
+    }
+
+    pub fn try_new_in_range(value: i32, min: i32, max: i32) -> Result<Guess, String> {
+        if !(min..=max).contains(&value) {
+            return Err(format!(
+                "Guess value must be between {} and {}, got {}.",
+                min, max, value
+            ));
         }
 
-        Guess { value }
+        Ok(Guess { value })
+    }
+
+    // value is private so the only way to get a Guess is through a
+    // constructor that checks it
+    pub fn value(&self) -> i32 {
+        self.value
     }
 }
 
@@ -134,6 +158,42 @@ mod tests {
         Guess::new(110);
     }
 
+    #[test]
+    fn guess_in_range_accepts_its_bounds() {
+        assert_eq!(Guess::new_in_range(-5, -5, 5).value, -5);
+        assert_eq!(Guess::new_in_range(5, -5, 5).value, 5);
+        assert_eq!(Guess::new_in_range(0, -5, 5).value, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Guess value must be between -5 and 5, got -6.")]
+    fn guess_in_range_below_min() {
+        Guess::new_in_range(-6, -5, 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "Guess value must be between -5 and 5, got 6.")]
+    fn guess_in_range_above_max() {
+        Guess::new_in_range(6, -5, 5);
+    }
+
+    #[test]
+    fn try_new_accepts_valid_guess() {
+        let guess = Guess::try_new(42).unwrap();
+        assert_eq!(guess.value(), 42);
+    }
+
+    #[test]
+    fn try_new_rejects_invalid_guess() {
+        assert_eq!(
+            Guess::try_new(0).err(),
+            Some(String::from(
+                "Guess value must be between 1 and 100, got 0."
+            ))
+        );
+        assert!(Guess::try_new(101).is_err());
+    }
+
     // Writing tests that return a Result<T, E> allows us to use the ?
     // operator in the body of tests. This might be useful when we want to run
     // a few operations in our test and fail if any of them return an Err
@@ -2,6 +2,273 @@
 // heap (can grow or shrink as program runs, rather than being known at compile
 // time).
 use std::collections::HashMap;
+use std::hash::Hash;
+
+// Splits a slice into owned chunks of the given size (the last one may be
+// smaller). slice::chunks already yields borrowed sub-slices; cloning each one
+// into its own Vec gives us data that can outlive the original slice. Panics
+// if size is 0, the same contract as slice::chunks
+fn chunk_into<T: Clone>(items: &[T], size: usize) -> Vec<Vec<T>> {
+    if size == 0 {
+        panic!("chunk size must be non-zero");
+    }
+    items.chunks(size).map(|chunk| chunk.to_vec()).collect()
+}
+
+// Averages over each sliding window of the given size, so the output has
+// data.len() - window + 1 values. Rather than re-summing every window (O(n*w)),
+// we keep a running sum: add the value entering the window and subtract the
+// one leaving it. Returns an empty Vec when no full window fits
+fn moving_average(data: &[f64], window: usize) -> Vec<f64> {
+    if window == 0 || window > data.len() {
+        return Vec::new();
+    }
+    let mut sum: f64 = data[..window].iter().sum();
+    let mut averages = Vec::with_capacity(data.len() - window + 1);
+    averages.push(sum / window as f64);
+    for i in window..data.len() {
+        sum += data[i] - data[i - window];
+        averages.push(sum / window as f64);
+    }
+    averages
+}
+
+// A Vec makes a handy stack: push onto the end, pop off the end. Every opener
+// is pushed, and every closer must match whatever opener is on top of the
+// stack. Anything other than a bracket is ignored
+fn is_balanced(s: &str) -> bool {
+    let mut stack = Vec::new();
+    for c in s.chars() {
+        match c {
+            '(' | '[' | '{' => stack.push(c),
+            ')' | ']' | '}' => {
+                let expected = match c {
+                    ')' => '(',
+                    ']' => '[',
+                    _ => '{',
+                };
+                if stack.pop() != Some(expected) {
+                    return false; // wrong opener on top, or nothing open at all
+                }
+            }
+            _ => (),
+        }
+    }
+    stack.is_empty() // anything left over was never closed
+}
+
+// Binary search over a slice that's sorted by key(item), like std's
+// binary_search_by_key, except target is passed by reference (handy when K is
+// something like a String we don't want to clone). Each step compares the key
+// of the middle element and throws away the half target can't be in, so it
+// takes O(log n) comparisons. Ok(i) means sorted[i] has the target key (if
+// several do, it may be any of them); Err(i) is where target would have to be
+// inserted to keep the slice sorted
+fn binary_search_by_key_ref<T, K: Ord, F: Fn(&T) -> K>(
+    sorted: &[T],
+    target: &K,
+    key: F,
+) -> Result<usize, usize> {
+    let mut lo = 0;
+    let mut hi = sorted.len(); // search sorted[lo..hi]
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        match key(&sorted[mid]).cmp(target) {
+            std::cmp::Ordering::Less => lo = mid + 1,
+            std::cmp::Ordering::Greater => hi = mid,
+            std::cmp::Ordering::Equal => return Ok(mid),
+        }
+    }
+    Err(lo)
+}
+
+// Splits text into sentences, each ending with its terminator ('.', '!' or
+// '?', or a run of them like "?!" or "..."). Every sentence is a slice of text
+// with the surrounding whitespace trimmed off, so nothing is copied. A
+// terminator only ends a sentence when it's followed by whitespace or the end
+// of the text, which keeps "3.14" or "example.com" in one piece. That's as far
+// as the abbreviation handling goes though: "Mr. Smith" still splits after
+// "Mr." since telling that apart needs a list of known abbreviations. Any text
+// after the last terminator comes out as a final, unterminated sentence
+pub fn sentences(text: &str) -> impl Iterator<Item = &str> {
+    let is_terminator = |c: char| matches!(c, '.' | '!' | '?');
+    let mut rest = text;
+    std::iter::from_fn(move || {
+        rest = rest.trim_start();
+        if rest.is_empty() {
+            return None;
+        }
+        let mut end = rest.len();
+        let mut search_from = 0;
+        while let Some(i) = rest[search_from..].find(is_terminator) {
+            let start = search_from + i;
+            // swallow the whole run of terminators
+            let run_end = rest[start..]
+                .find(|c: char| !is_terminator(c))
+                .map_or(rest.len(), |j| start + j);
+            if run_end == rest.len() || rest[run_end..].starts_with(char::is_whitespace) {
+                end = run_end;
+                break;
+            }
+            search_from = run_end;
+        }
+        let (sentence, tail) = rest.split_at(end);
+        rest = tail;
+        Some(sentence.trim_end())
+    })
+}
+
+// Parses every non-blank line of s into a T. collect() can gather an iterator
+// of Results into a Result<Vec<T>, E>: it stops at the first Err and returns
+// that, otherwise we get all the parsed values
+fn parse_lines<T: std::str::FromStr>(s: &str) -> Result<Vec<T>, T::Err> {
+    s.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::parse)
+        .collect()
+}
+
+// Counts how often each word appears, ignoring case and any punctuation
+// around a word (but not inside it, so "don't" stays one word). This is the
+// entry API at work: or_insert hands back a &mut to the count, whether it was
+// already there or just inserted as 0
+fn word_frequencies(text: &str) -> HashMap<String, u32> {
+    let mut counts = HashMap::new();
+    for word in text.to_lowercase().split_whitespace() {
+        let word = word.trim_matches(|c: char| !c.is_alphanumeric());
+        if word.is_empty() {
+            continue; // it was all punctuation, e.g. a lone "-"
+        }
+        *counts.entry(word.to_string()).or_insert(0) += 1;
+    }
+    counts
+}
+
+// The middle value once sorted, or the mean of the two middle values when
+// there's an even number of them. Sorts nums in place, which is why it needs a
+// mutable slice (a &mut Vec<i32> coerces to one)
+fn median(nums: &mut [i32]) -> Option<f64> {
+    if nums.is_empty() {
+        return None;
+    }
+    nums.sort_unstable();
+    let mid = nums.len() / 2;
+    if nums.len() % 2 == 1 {
+        Some(f64::from(nums[mid]))
+    } else {
+        // convert first so adding two large values can't overflow
+        Some((f64::from(nums[mid - 1]) + f64::from(nums[mid])) / 2.0)
+    }
+}
+
+// The most frequent value. When several values tie, any of them may be
+// returned
+fn mode(nums: &[i32]) -> Option<i32> {
+    let mut counts = HashMap::new();
+    for &n in nums {
+        *counts.entry(n).or_insert(0) += 1;
+    }
+    counts
+        .into_iter()
+        .max_by_key(|&(_, count)| count)
+        .map(|(n, _)| n)
+}
+
+// "first" becomes "irst-fay" and "apple" becomes "apple-hay". Strings are
+// UTF-8, so the first letter may take more than one byte; taking it with
+// chars() rather than slicing at byte 1 means we never split a character in
+// half. Words are separated by single spaces in the output
+fn to_pig_latin(text: &str) -> String {
+    let is_vowel = |c: char| matches!(c.to_ascii_lowercase(), 'a' | 'e' | 'i' | 'o' | 'u');
+    text.split_whitespace()
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) if is_vowel(first) => format!("{}-hay", word),
+                // as_str gives back whatever the iterator hasn't yielded yet
+                Some(first) => format!("{}-{}ay", chars.as_str(), first),
+                None => String::new(), // split_whitespace never yields ""
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// Uppercases the first letter of every word and lowercases the rest, leaving
+// the whitespace between words exactly as it was. Changing case can change
+// how many chars there are ('ß' uppercases to "SS"), which is why
+// to_uppercase and to_lowercase return iterators rather than a single char
+fn capitalize_words(s: &str) -> String {
+    let mut capitalized = String::with_capacity(s.len());
+    let mut at_word_start = true;
+    for c in s.chars() {
+        if c.is_whitespace() {
+            capitalized.push(c);
+            at_word_start = true;
+        } else if at_word_start {
+            capitalized.extend(c.to_uppercase());
+            at_word_start = false;
+        } else {
+            capitalized.extend(c.to_lowercase());
+        }
+    }
+    capitalized
+}
+
+// Buckets items by whatever key_fn computes for them. Within a bucket, items
+// keep their original order. or_insert_with only builds the empty Vec when the
+// key isn't there yet, unlike or_insert(Vec::new()) which would build one on
+// every call
+fn group_by<T, K: Eq + Hash, F: Fn(&T) -> K>(items: Vec<T>, key_fn: F) -> HashMap<K, Vec<T>> {
+    let mut groups = HashMap::new();
+    for item in items {
+        groups
+            .entry(key_fn(&item))
+            .or_insert_with(Vec::new)
+            .push(item);
+    }
+    groups
+}
+
+// The classic "add Sally to Engineering" exercise: a map from each department
+// to the people in it. Names are stored in the order they were added and only
+// sorted when listed
+#[derive(Debug, Default)]
+struct Company {
+    departments: HashMap<String, Vec<String>>,
+}
+
+impl Company {
+    fn new() -> Company {
+        Company::default()
+    }
+
+    fn add(&mut self, name: &str, dept: &str) {
+        self.departments
+            .entry(dept.to_string())
+            .or_default()
+            .push(name.to_string());
+    }
+
+    // Empty if nobody is in the department (or it doesn't exist)
+    fn list_department(&self, dept: &str) -> Vec<String> {
+        let mut names = self.departments.get(dept).cloned().unwrap_or_default();
+        names.sort();
+        names
+    }
+
+    // Every (department, name) pair, sorted by department and then by name
+    fn list_all(&self) -> Vec<(String, String)> {
+        let mut everyone: Vec<(String, String)> = self
+            .departments
+            .iter()
+            .flat_map(|(dept, names)| names.iter().map(move |name| (dept.clone(), name.clone())))
+            .collect();
+        everyone.sort();
+        everyone
+    }
+}
 
 fn main() {
     // needs type annotation since we haven't inserted any items yet
@@ -127,4 +394,283 @@ fn main() {
     for (key, val) in &scores2 {
         println!("{}: {}", key, val);
     }
+
+    println!("Chunks of 2: {:?}", chunk_into(&v1, 2));
+    println!(
+        "Moving average: {:?}",
+        moving_average(&[1.0, 2.0, 3.0, 4.0], 2)
+    );
+    println!("{{[()]}} is balanced: {}", is_balanced("{[()]}"));
+    let team_scores = [("Blue", 10), ("Yellow", 20), ("Red", 40)];
+    match binary_search_by_key_ref(&team_scores, &20, |&(_, score)| score) {
+        Ok(i) => println!("{} scored 20", team_scores[i].0),
+        Err(i) => println!("Nobody scored 20, it would go at index {}", i),
+    }
+    for sentence in sentences("Vectors grow. Do HashMaps? Yes!") {
+        println!("Sentence: {}", sentence);
+    }
+    let parsed: Result<Vec<u8>, _> = parse_lines("1\n 2\n\n3\n");
+    println!("Parsed lines: {:?}", parsed);
+    let freqs = word_frequencies("The cat saw the other cat. The end!");
+    println!("\"the\" appears {} times", freqs["the"]);
+    println!("In pig latin: {}", to_pig_latin("eat your vegetables"));
+    println!("Capitalized: {}", capitalize_words("the rUST book"));
+    let by_length = group_by(vec!["vec", "map", "set", "deque"], |word| word.len());
+    println!("Three-letter collections: {:?}", by_length[&3]);
+
+    let mut company = Company::new();
+    company.add("Sally", "Engineering");
+    company.add("Amir", "Sales");
+    company.add("Bob", "Engineering");
+    println!("Engineering: {:?}", company.list_department("Engineering"));
+    for (dept, name) in company.list_all() {
+        println!("{} works in {}", name, dept);
+    }
+    let mut readings = vec![7, 3, 9, 3, 5, 3];
+    println!(
+        "Median is {:?}, mode is {:?}",
+        median(&mut readings),
+        mode(&readings)
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunk_into_keeps_short_last_chunk() {
+        assert_eq!(
+            chunk_into(&[1, 2, 3, 4, 5], 2),
+            vec![vec![1, 2], vec![3, 4], vec![5]]
+        );
+    }
+
+    #[test]
+    fn chunk_into_empty_input() {
+        let empty: [i32; 0] = [];
+        assert!(chunk_into(&empty, 3).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "chunk size must be non-zero")]
+    fn chunk_into_rejects_zero_size() {
+        chunk_into(&[1, 2, 3], 0);
+    }
+
+    #[test]
+    fn moving_average_over_known_series() {
+        let data = [2.0, 4.0, 6.0, 8.0, 10.0, 3.0];
+        let expected = [4.0, 6.0, 8.0, 7.0];
+        let res = moving_average(&data, 3);
+        assert_eq!(res.len(), expected.len());
+        for (r, e) in res.iter().zip(expected.iter()) {
+            assert!((r - e).abs() < 1e-9, "expected {}, got {}", e, r);
+        }
+    }
+
+    #[test]
+    fn moving_average_degenerate_windows() {
+        let data = [1.0, 2.0, 3.0];
+        assert!(moving_average(&data, 0).is_empty());
+        assert!(moving_average(&data, 4).is_empty());
+        assert_eq!(moving_average(&data, 3), vec![2.0]);
+        assert_eq!(moving_average(&data, 1), data.to_vec());
+    }
+
+    #[test]
+    fn balanced_brackets() {
+        assert!(is_balanced("{[()]}"));
+        assert!(is_balanced("fn main() { let v = vec![(1, 2)]; }"));
+        assert!(is_balanced("()[]{}"));
+    }
+
+    #[test]
+    fn wrong_closer_is_unbalanced() {
+        assert!(!is_balanced("(]"));
+        assert!(!is_balanced("{[(])}"));
+    }
+
+    #[test]
+    fn unclosed_or_unopened_is_unbalanced() {
+        assert!(!is_balanced("(("));
+        assert!(!is_balanced("{[]"));
+        assert!(!is_balanced(")"));
+        assert!(!is_balanced("())("));
+    }
+
+    #[test]
+    fn empty_string_is_balanced() {
+        assert!(is_balanced(""));
+        assert!(is_balanced("no brackets here"));
+    }
+
+    #[test]
+    fn sentences_keep_terminators_and_trim() {
+        let text = "  Hello there. How are you?  I'm fine!\nPi is 3.14... Wow?! trailing bit ";
+        let found: Vec<&str> = sentences(text).collect();
+        assert_eq!(
+            found,
+            vec![
+                "Hello there.",
+                "How are you?",
+                "I'm fine!",
+                "Pi is 3.14...",
+                "Wow?!",
+                "trailing bit",
+            ]
+        );
+        // each sentence is a slice of the original text, not a copy
+        let range = text.as_ptr() as usize..text.as_ptr() as usize + text.len();
+        for sentence in found {
+            assert!(range.contains(&(sentence.as_ptr() as usize)));
+        }
+        assert_eq!(sentences("   ").next(), None);
+    }
+
+    struct Book {
+        title: String,
+        year: u32,
+    }
+
+    fn books() -> Vec<Book> {
+        [("Dune", 1965), ("Neuromancer", 1984), ("Hyperion", 1989)]
+            .iter()
+            .map(|&(title, year)| Book {
+                title: String::from(title),
+                year,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn binary_search_by_key_ref_finds_present_keys() {
+        let books = books();
+        let by_year = |b: &Book| b.year;
+        assert_eq!(binary_search_by_key_ref(&books, &1965, by_year), Ok(0));
+        assert_eq!(binary_search_by_key_ref(&books, &1989, by_year), Ok(2));
+        // a key that isn't a plain field: the decade the book came out in
+        let i = binary_search_by_key_ref(&books, &1980, |b| b.year / 10 * 10).unwrap();
+        assert_eq!(books[i].title, "Neuromancer");
+    }
+
+    #[test]
+    fn binary_search_by_key_ref_gives_insert_position() {
+        let books = books();
+        let by_year = |b: &Book| b.year;
+        assert_eq!(binary_search_by_key_ref(&books, &1950, by_year), Err(0));
+        assert_eq!(binary_search_by_key_ref(&books, &1970, by_year), Err(1));
+        assert_eq!(binary_search_by_key_ref(&books, &2000, by_year), Err(3));
+        assert_eq!(binary_search_by_key_ref(&[], &1, by_year), Err(0));
+    }
+
+    #[test]
+    fn parse_lines_skips_blanks_and_trims() {
+        let nums: Vec<i32> = parse_lines("  1\n\n-2  \n   \n30").unwrap();
+        assert_eq!(nums, vec![1, -2, 30]);
+        assert_eq!(parse_lines::<i32>("").unwrap(), Vec::<i32>::new());
+    }
+
+    #[test]
+    fn parse_lines_reports_first_bad_line() {
+        let err = parse_lines::<i32>("1\ntwo\n3.5").unwrap_err();
+        assert_eq!(err, "two".parse::<i32>().unwrap_err());
+    }
+
+    #[test]
+    fn word_frequencies_ignores_case_and_punctuation() {
+        let freqs = word_frequencies("The cat -- the CAT! -- saw \"the\" dog's toy.");
+        assert_eq!(freqs["the"], 3);
+        assert_eq!(freqs["cat"], 2);
+        assert_eq!(freqs["dog's"], 1);
+        assert_eq!(freqs["toy"], 1);
+        assert_eq!(freqs.len(), 5); // the, cat, saw, dog's, toy
+        assert!(word_frequencies("  ...  ").is_empty());
+    }
+
+    #[test]
+    fn median_of_odd_and_even_lengths() {
+        assert_eq!(median(&mut [5, 1, 3]), Some(3.0));
+        assert_eq!(median(&mut [4, 1, 3, 2]), Some(2.5));
+        let big = i32::MAX;
+        assert_eq!(median(&mut [big, big]), Some(f64::from(big)));
+        assert_eq!(median(&mut []), None);
+    }
+
+    #[test]
+    fn mode_finds_most_frequent_value() {
+        assert_eq!(mode(&[1, 2, 2, 3, 2, 1]), Some(2));
+        assert_eq!(mode(&[-4]), Some(-4));
+        assert_eq!(mode(&[]), None);
+    }
+
+    #[test]
+    fn pig_latin_vowel_start() {
+        assert_eq!(to_pig_latin("apple"), "apple-hay");
+        assert_eq!(to_pig_latin("Orange"), "Orange-hay");
+    }
+
+    #[test]
+    fn pig_latin_consonant_start() {
+        assert_eq!(to_pig_latin("first"), "irst-fay");
+        // multi-byte first letter
+        assert_eq!(to_pig_latin("здравствуйте"), "дравствуйте-зay");
+    }
+
+    #[test]
+    fn pig_latin_multiple_words() {
+        assert_eq!(
+            to_pig_latin("  hello   apple world "),
+            "ello-hay apple-hay orld-way"
+        );
+        assert_eq!(to_pig_latin(""), "");
+    }
+
+    #[test]
+    fn capitalize_words_fixes_mixed_case() {
+        assert_eq!(capitalize_words("hELLO wORLD"), "Hello World");
+        assert_eq!(capitalize_words(" a  b\tc "), " A  B\tC ");
+        assert_eq!(capitalize_words(""), "");
+    }
+
+    #[test]
+    fn capitalize_words_multi_byte_first_letter() {
+        assert_eq!(capitalize_words("élan ÉCOLE"), "Élan École");
+        assert_eq!(capitalize_words("ßuper"), "SSuper");
+    }
+
+    #[test]
+    fn group_by_even_and_odd() {
+        let groups = group_by((1..=7).collect(), |n| n % 2 == 0);
+        assert_eq!(groups[&true], vec![2, 4, 6]);
+        assert_eq!(groups[&false], vec![1, 3, 5, 7]);
+        assert_eq!(groups.len(), 2);
+        assert!(group_by(Vec::<i32>::new(), |n| *n).is_empty());
+    }
+
+    #[test]
+    fn company_lists_departments_sorted() {
+        let mut company = Company::new();
+        company.add("Sally", "Engineering");
+        company.add("Amir", "Sales");
+        company.add("Bob", "Engineering");
+        company.add("Alice", "Engineering");
+        assert_eq!(
+            company.list_department("Engineering"),
+            vec!["Alice", "Bob", "Sally"]
+        );
+        assert_eq!(company.list_department("Sales"), vec!["Amir"]);
+        assert!(company.list_department("Marketing").is_empty());
+
+        let pair = |dept: &str, name: &str| (dept.to_string(), name.to_string());
+        assert_eq!(
+            company.list_all(),
+            vec![
+                pair("Engineering", "Alice"),
+                pair("Engineering", "Bob"),
+                pair("Engineering", "Sally"),
+                pair("Sales", "Amir"),
+            ]
+        );
+    }
 }
@@ -103,6 +103,94 @@ fn message_passing() {
     // transmitter will go out of scope, which breaks the receive loop
 }
 
+// recv() waits forever, which is a problem if the sender is slow or stuck.
+// recv_timeout() waits at most the given Duration and returns
+// Err(RecvTimeoutError::Timeout) if nothing arrived. To give up at an overall
+// deadline rather than after one quiet gap, each wait is only as long as the
+// time left. The sender is never joined: once the receiver is dropped its
+// sends start failing, and that's its cue to stop.
+// Returns how many messages arrived in time.
+fn message_passing_with_deadline(count: u32, send_interval: Duration, timeout: Duration) -> usize {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        for i in 0..count {
+            thread::sleep(send_interval);
+            if tx.send(i).is_err() {
+                break; // nobody is listening any more
+            }
+        }
+    });
+    let deadline = Instant::now() + timeout;
+    let mut received = 0;
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        match rx.recv_timeout(remaining) {
+            Ok(_) => received += 1,
+            // either Timeout (out of time) or Disconnected (the sender
+            // finished early); both mean there's nothing more to wait for
+            Err(_) => break,
+        }
+    }
+    received
+}
+
+// The standard library has no way to block on two receivers at once (crates
+// like crossbeam provide a select! for that). A poor man's version: poll each
+// receiver with try_recv, which never blocks, and nap briefly whenever a
+// whole pass turns up nothing so we aren't spinning the CPU. Messages come out
+// in whatever order they're found, until both channels are closed
+fn drain_two<T>(rx1: mpsc::Receiver<T>, rx2: mpsc::Receiver<T>) -> Vec<T> {
+    let mut receivers = vec![rx1, rx2];
+    let mut drained = Vec::new();
+    while !receivers.is_empty() {
+        let mut got_any = false;
+        // retain drops receivers whose senders are all gone
+        receivers.retain(|rx| match rx.try_recv() {
+            Ok(val) => {
+                drained.push(val);
+                got_any = true;
+                true
+            }
+            Err(mpsc::TryRecvError::Empty) => true,
+            Err(mpsc::TryRecvError::Disconnected) => false,
+        });
+        if !got_any && !receivers.is_empty() {
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+    drained
+}
+
+// mpsc::channel() has an unbounded buffer: send() never blocks, so a fast
+// producer paired with a slow consumer just keeps piling messages up in
+// memory. mpsc::sync_channel(capacity) holds at most capacity messages, and
+// send() blocks once they're all in use until the receiver takes one. That
+// blocking is backpressure: the producer is forced to slow down to the
+// consumer's pace. With a capacity of 0, every send waits for a matching recv
+// (a "rendezvous" channel).
+// Returns what the consumer received and how long the producer took to get
+// everything sent.
+fn bounded_message_passing(
+    capacity: usize,
+    count: u32,
+    consume_delay: Duration,
+) -> (Vec<u32>, Duration) {
+    let (tx, rx) = mpsc::sync_channel(capacity);
+    let producer = thread::spawn(move || {
+        let start = Instant::now();
+        for i in 0..count {
+            tx.send(i).unwrap(); // blocks while the buffer is full
+        }
+        start.elapsed()
+    });
+    let mut received = Vec::new();
+    for val in rx {
+        thread::sleep(consume_delay); // a slow consumer
+        received.push(val);
+    }
+    (received, producer.join().unwrap())
+}
+
 // Rust's type system and ownership rules greatly assist in getting shared
 // state concurrency correct. Let's look at mutexes in Rust, which are a sync
 // primitive that allow threads to access shared memory one at a time. The two
@@ -145,6 +233,475 @@ fn shared_state_concurrency() {
     println!("Final count = {}", *cnt.lock().unwrap());
 }
 
+// The same counter as in shared_state_concurrency, but with an atomic integer
+// instead of a Mutex<i32>. fetch_add does the read-modify-write as a single
+// indivisible CPU operation, so there's no lock to take, no guard, and no
+// chance of poisoning. Atomics are preferable when the shared state is a
+// single integer or flag; once several values have to change together (or
+// the update is more than one operation), a Mutex is the right tool. SeqCst
+// is the strictest (and simplest to reason about) memory ordering
+fn atomic_counter(threads: usize) -> usize {
+    let cnt = Arc::new(AtomicUsize::new(0));
+    let handles: Vec<_> = (0..threads)
+        .map(|_| {
+            let cnt = Arc::clone(&cnt);
+            thread::spawn(move || {
+                cnt.fetch_add(1, AtomicOrdering::SeqCst);
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    cnt.load(AtomicOrdering::SeqCst)
+}
+
+// A Condvar lets a thread sleep until another thread tells it that the data
+// behind a Mutex has changed, instead of busy-waiting by locking and checking
+// over and over. The Condvar is always paired with the Mutex guarding that
+// data, so they're shared together in one Arc. The done flag lives under the
+// same lock as the items; that way a consumer can't check it, miss the final
+// notify, and then sleep forever
+struct WorkQueue {
+    items: Vec<i32>,
+    done: bool,
+}
+
+// Returns every item the consumers took, in whatever order they took them
+fn producer_consumer(count: i32, consumers: usize) -> Vec<i32> {
+    let shared = Arc::new((
+        Mutex::new(WorkQueue {
+            items: Vec::new(),
+            done: false,
+        }),
+        Condvar::new(),
+    ));
+    let handles: Vec<_> = (0..consumers)
+        .map(|_| {
+            let shared = Arc::clone(&shared);
+            thread::spawn(move || {
+                let (queue, available) = &*shared;
+                let mut consumed = Vec::new();
+                loop {
+                    // wait_while releases the lock while asleep and checks
+                    // the condition again on every wakeup, which also
+                    // handles spurious wakeups
+                    let mut queue = available
+                        .wait_while(queue.lock().unwrap(), |q| q.items.is_empty() && !q.done)
+                        .unwrap();
+                    if queue.items.is_empty() {
+                        break; // done, and nothing left to take
+                    }
+                    consumed.append(&mut queue.items);
+                }
+                consumed
+            })
+        })
+        .collect();
+
+    let (queue, available) = &*shared;
+    for i in 0..count {
+        queue.lock().unwrap().items.push(i);
+        available.notify_one();
+    }
+    queue.lock().unwrap().done = true;
+    available.notify_all(); // every consumer needs to see the flag
+
+    handles
+        .into_iter()
+        .flat_map(|handle| handle.join().unwrap())
+        .collect()
+}
+
+// Renders a Duration using the largest unit that keeps it readable: whole
+// microseconds below a millisecond, whole milliseconds below a second, and
+// seconds to three decimal places otherwise
+fn format_elapsed(d: Duration) -> String {
+    if d < Duration::from_millis(1) {
+        format!("{}µs", d.as_micros())
+    } else if d < Duration::from_secs(1) {
+        format!("{}ms", d.as_millis())
+    } else {
+        format!("{:.3}s", d.as_secs_f64())
+    }
+}
+
+// Instant is a monotonic clock reading, so the difference between two
+// Instants is a reliable measure of elapsed time (unlike SystemTime, which
+// can jump around if the system clock is adjusted)
+use std::time::Instant;
+
+fn timed_threading() {
+    let start = Instant::now();
+    let handles: Vec<_> = (1..=4)
+        .map(|i| {
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(10 * i));
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    // the threads sleep concurrently, so this should be about 40ms, not 100ms
+    println!("Batch of threads took {}", format_elapsed(start.elapsed()));
+}
+
+// A scoped timer: the clock starts when a Timer is created and stops when it's
+// dropped (i.e., when it goes out of scope), so timing a section of code is
+// just a matter of creating a Timer at the top of it. Each measurement is
+// printed and also recorded in TIMINGS. A Mutex can be used in a static
+// because Mutex::new is a const fn; the Mutex is what makes it safe for any
+// thread to record into the same log
+static TIMINGS: Mutex<Vec<(String, Duration)>> = Mutex::new(Vec::new());
+
+struct Timer {
+    label: String,
+    start: Instant,
+}
+
+impl Timer {
+    fn new(label: &str) -> Timer {
+        Timer {
+            label: String::from(label),
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Drop for Timer {
+    fn drop(&mut self) {
+        let elapsed = self.start.elapsed();
+        println!("[{}] took {}", self.label, format_elapsed(elapsed));
+        TIMINGS.lock().unwrap().push((self.label.clone(), elapsed));
+    }
+}
+
+// Times a closure and hands back whatever it returned. The Timer is dropped
+// at the end of this function, after f() has been evaluated
+fn time<T, F: FnOnce() -> T>(label: &str, f: F) -> T {
+    let _timer = Timer::new(label); // not "_", which would drop immediately
+    f()
+}
+
+// Merge sort splits naturally into independent halves, so the two halves can
+// be sorted on different threads. thread::scope guarantees every thread spawned
+// inside it is joined before it returns, which is what lets the threads borrow
+// the two halves of data (non-overlapping &mut slices from split_at_mut)
+// instead of needing owned copies. Each level of recursion halves the thread
+// budget; once it runs out, or the slice is too small to be worth a thread,
+// we fall back to the standard library's sequential sort. Taking a slice
+// rather than &mut Vec<T> means a &mut Vec<T> still works (deref coercion)
+const MIN_PARALLEL_LEN: usize = 1024;
+
+fn parallel_merge_sort<T: Ord + Send + Clone>(data: &mut [T], threads: usize) {
+    if threads <= 1 || data.len() < MIN_PARALLEL_LEN {
+        data.sort();
+        return;
+    }
+    let mid = data.len() / 2;
+    let (left, right) = data.split_at_mut(mid);
+    thread::scope(|s| {
+        s.spawn(|| parallel_merge_sort(left, threads / 2));
+        // this thread sorts the other half rather than sitting idle
+        parallel_merge_sort(right, threads - threads / 2);
+    });
+    let merged = merge(&data[..mid], &data[mid..]);
+    data.clone_from_slice(&merged);
+}
+
+// Merges two sorted slices into a new sorted Vec. Taking from the left on ties
+// keeps the sort stable
+fn merge<T: Ord + Clone>(left: &[T], right: &[T]) -> Vec<T> {
+    let mut merged = Vec::with_capacity(left.len() + right.len());
+    let (mut i, mut j) = (0, 0);
+    while i < left.len() && j < right.len() {
+        if right[j] < left[i] {
+            merged.push(right[j].clone());
+            j += 1;
+        } else {
+            merged.push(left[i].clone());
+            i += 1;
+        }
+    }
+    merged.extend_from_slice(&left[i..]);
+    merged.extend_from_slice(&right[j..]);
+    merged
+}
+
+// Scoped threads (std::thread::scope, stable since Rust 1.63) are guaranteed
+// to be joined before scope() returns, so unlike thread::spawn they may borrow
+// data from the enclosing stack frame: no Arc, no cloning into each thread.
+// Here each thread reads its own chunk of data and writes its partial sum into
+// its own slot of partials. The closures are still `move`, but all that moves
+// into them is the pair of references, not the data. (usize::div_ceil needs
+// Rust 1.73, so that's the real minimum for this function.)
+fn scoped_threading(data: &[i64], threads: usize) -> i64 {
+    let chunk_size = data.len().div_ceil(threads.max(1)).max(1);
+    let mut partials = vec![0; data.len().div_ceil(chunk_size)];
+    thread::scope(|s| {
+        for (chunk, partial) in data.chunks(chunk_size).zip(partials.iter_mut()) {
+            s.spawn(move || *partial = chunk.iter().sum());
+        }
+    });
+    // every thread has been joined by now, so partials can be read again
+    partials.iter().sum()
+}
+
+// A thread's closure can return a value, which join() hands back wrapped in a
+// Result (Err if the thread panicked). thread::spawn needs a 'static closure,
+// so unlike scoped_threading each thread gets its own copy of its chunk, and
+// the partial sums come back through the JoinHandles instead of shared slots
+fn parallel_sum(data: &[i64], chunks: usize) -> i64 {
+    let chunk_size = data.len().div_ceil(chunks.max(1)).max(1);
+    let handles: Vec<thread::JoinHandle<i64>> = data
+        .chunks(chunk_size)
+        .map(|chunk| {
+            let chunk = chunk.to_vec();
+            thread::spawn(move || chunk.iter().sum())
+        })
+        .collect();
+    handles
+        .into_iter()
+        .map(|handle| handle.join().unwrap())
+        .sum()
+}
+
+// A priority scheduler: jobs wait in a BinaryHeap (a max-heap, so pop() always
+// hands back the largest element) and worker threads take the highest-priority
+// one whenever they're free. Workers with nothing to do block on a Condvar
+// rather than spinning; submit() wakes one of them after pushing a job. Jobs
+// with equal priority run in the order they were submitted, which is what the
+// sequence number is for
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering as AtomicOrdering};
+use std::sync::Condvar;
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+struct PrioritizedJob {
+    priority: u32,
+    seq: u64,
+    job: Job,
+}
+
+// The heap only needs to order jobs, so equality and ordering are defined by
+// (priority, seq) alone. A lower seq was submitted earlier, so it's "greater"
+impl Ord for PrioritizedJob {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+impl PartialOrd for PrioritizedJob {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for PrioritizedJob {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for PrioritizedJob {}
+
+struct Scheduler {
+    queue: Arc<Mutex<BinaryHeap<PrioritizedJob>>>,
+    available: Arc<Condvar>,
+    shutting_down: Arc<AtomicBool>,
+    next_seq: AtomicU64,
+    workers: Vec<thread::JoinHandle<()>>,
+}
+
+impl Scheduler {
+    fn new(workers: usize) -> Scheduler {
+        assert!(workers > 0, "a scheduler needs at least one worker");
+        let queue = Arc::new(Mutex::new(BinaryHeap::new()));
+        let available = Arc::new(Condvar::new());
+        let shutting_down = Arc::new(AtomicBool::new(false));
+        let workers = (0..workers)
+            .map(|_| {
+                let queue = Arc::clone(&queue);
+                let available = Arc::clone(&available);
+                let shutting_down = Arc::clone(&shutting_down);
+                thread::spawn(move || Scheduler::work(&queue, &available, &shutting_down))
+            })
+            .collect();
+        Scheduler {
+            queue,
+            available,
+            shutting_down,
+            next_seq: AtomicU64::new(0),
+            workers,
+        }
+    }
+
+    fn submit(&self, priority: u32, job: Job) {
+        let seq = self.next_seq.fetch_add(1, AtomicOrdering::Relaxed);
+        self.queue
+            .lock()
+            .unwrap()
+            .push(PrioritizedJob { priority, seq, job });
+        self.available.notify_one();
+    }
+
+    // Each worker loops until the scheduler is shutting down and there's
+    // nothing left to run. Condvar::wait releases the lock while sleeping and
+    // reacquires it on wakeup, so the loop re-checks the heap each time (wakeups
+    // can be spurious). The job itself runs after the guard is dropped so other
+    // workers aren't locked out while it executes
+    fn work(
+        queue: &Mutex<BinaryHeap<PrioritizedJob>>,
+        available: &Condvar,
+        shutting_down: &AtomicBool,
+    ) {
+        loop {
+            let mut jobs = queue.lock().unwrap();
+            let next = loop {
+                if let Some(next) = jobs.pop() {
+                    break next;
+                }
+                if shutting_down.load(AtomicOrdering::SeqCst) {
+                    return;
+                }
+                jobs = available.wait(jobs).unwrap();
+            };
+            drop(jobs);
+            (next.job)();
+        }
+    }
+}
+
+// Dropping the scheduler lets the workers finish whatever is still queued and
+// then waits for them to exit. The flag is set while holding the queue's lock
+// so a worker can't check it and then miss the wakeup before it starts waiting
+impl Drop for Scheduler {
+    fn drop(&mut self) {
+        {
+            let _jobs = self.queue.lock().unwrap();
+            self.shutting_down.store(true, AtomicOrdering::SeqCst);
+        }
+        self.available.notify_all();
+        for worker in self.workers.drain(..) {
+            worker.join().unwrap();
+        }
+    }
+}
+
+fn scheduling() {
+    let scheduler = Scheduler::new(2);
+    for priority in [1, 5, 3] {
+        scheduler.submit(
+            priority,
+            Box::new(move || println!("Ran job with priority {}", priority)),
+        );
+    }
+}
+
+// A thread pool keeps a fixed set of threads alive and hands them jobs over a
+// channel, so we don't pay for spawning a thread per job. The channel only has
+// one receiver, so the workers share it behind an Arc<Mutex<...>>; whichever
+// worker grabs the lock first takes the next job. To shut down, Drop sends
+// one Terminate per worker; each worker stops after receiving one, so every
+// worker gets exactly one and any jobs queued ahead of them still run
+struct ThreadPool {
+    workers: Vec<Worker>,
+    sender: mpsc::Sender<Message>,
+}
+
+enum Message {
+    NewJob(Job),
+    Terminate,
+}
+
+struct Worker {
+    id: usize,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl Worker {
+    fn new(id: usize, receiver: Arc<Mutex<mpsc::Receiver<Message>>>) -> Worker {
+        let thread = thread::spawn(move || loop {
+            // the temporary MutexGuard is dropped at the end of this
+            // statement, so the lock isn't held while the job runs
+            let message = receiver.lock().unwrap().recv();
+            match message {
+                Ok(Message::NewJob(job)) => job(),
+                // Err would mean the sender is gone without a Terminate
+                // having been sent. Drop doesn't do that, but stopping is
+                // still better than unwrapping and panicking
+                Ok(Message::Terminate) | Err(_) => break,
+            }
+        });
+        Worker {
+            id,
+            thread: Some(thread),
+        }
+    }
+}
+
+impl ThreadPool {
+    // A pool with no threads would accept jobs and never run them, so that's
+    // treated as a programming error
+    fn new(size: usize) -> ThreadPool {
+        assert!(size > 0, "ThreadPool needs at least one thread");
+        let (sender, receiver) = mpsc::channel();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let workers = (0..size)
+            .map(|id| Worker::new(id, Arc::clone(&receiver)))
+            .collect();
+        ThreadPool { workers, sender }
+    }
+
+    fn execute<F>(&self, f: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.sender.send(Message::NewJob(Box::new(f))).unwrap();
+    }
+}
+
+// One thread per core the OS says we can use. available_parallelism can fail
+// (e.g. on platforms where it isn't supported), in which case we settle for 1
+impl Default for ThreadPool {
+    fn default() -> ThreadPool {
+        let size = thread::available_parallelism().map_or(1, |n| n.get());
+        ThreadPool::new(size)
+    }
+}
+
+impl Drop for ThreadPool {
+    fn drop(&mut self) {
+        for _ in &self.workers {
+            self.sender.send(Message::Terminate).unwrap();
+        }
+        for worker in &mut self.workers {
+            if let Some(thread) = worker.thread.take() {
+                println!("Shutting down worker {}", worker.id);
+                thread.join().unwrap();
+            }
+        }
+    }
+}
+
+fn pooled_jobs() {
+    let pool = ThreadPool::default();
+    println!("Thread pool has {} workers", pool.workers.len());
+    let (tx, rx) = mpsc::channel();
+    for i in 0..4 {
+        let tx = tx.clone();
+        pool.execute(move || tx.send(i * i).unwrap());
+    }
+    drop(tx);
+    let total: i32 = rx.iter().sum();
+    println!("Sum of squares computed by the pool: {}", total);
+}
+
 // Send and Sync traits
 // If a type implements Send, then it means ownership of such a type can be
 // transferred between threads. As a rule of thumb, pretty much all primitives
@@ -162,5 +719,281 @@ fn shared_state_concurrency() {
 fn main() {
     basic_threading();
     message_passing();
+    let in_time =
+        message_passing_with_deadline(10, Duration::from_millis(500), Duration::from_secs(2));
+    println!("{} of 10 messages arrived before the deadline", in_time);
+    let (tx1, rx1) = mpsc::channel();
+    let (tx2, rx2) = mpsc::channel();
+    thread::spawn(move || {
+        for word in ["one", "two", "three"] {
+            tx1.send(word).unwrap();
+            thread::sleep(Duration::from_millis(30));
+        }
+    });
+    thread::spawn(move || {
+        for word in ["uno", "dos", "tres"] {
+            tx2.send(word).unwrap();
+            thread::sleep(Duration::from_millis(20));
+        }
+    });
+    println!("Drained from both channels: {:?}", drain_two(rx1, rx2));
+    let (received, blocked_for) = bounded_message_passing(2, 6, Duration::from_millis(50));
+    println!(
+        "Bounded channel delivered {:?}; the producer needed {:?} to send them",
+        received, blocked_for
+    );
     shared_state_concurrency();
+    println!("Final atomic count = {}", atomic_counter(10));
+    let consumed = producer_consumer(20, 3);
+    println!("Consumers took {} items", consumed.len());
+    timed_threading();
+    scheduling();
+    pooled_jobs();
+    let mut data: Vec<u32> = (0..100_000).rev().collect();
+    time("parallel merge sort", || parallel_merge_sort(&mut data, 4));
+    println!("First few sorted values: {:?}", &data[..5]);
+    let values: Vec<i64> = (1..=1000).collect();
+    println!("Scoped threads summed {}", scoped_threading(&values, 4));
+    println!("Spawned threads summed {}", parallel_sum(&values, 4));
+    let sum = time("summing", || (1..=1_000_000u64).sum::<u64>());
+    println!(
+        "Sum is {}, {} timing(s) recorded",
+        sum,
+        TIMINGS.lock().unwrap().len()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn receiver_gives_up_at_deadline() {
+        let start = Instant::now();
+        let received = message_passing_with_deadline(
+            100,
+            Duration::from_millis(20),
+            Duration::from_millis(100),
+        );
+        assert!(received < 100);
+        // nowhere near the two seconds the sender would need to finish
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn receiver_stops_early_when_sender_finishes() {
+        let received =
+            message_passing_with_deadline(5, Duration::from_millis(1), Duration::from_secs(10));
+        assert_eq!(received, 5);
+    }
+
+    #[test]
+    fn drain_two_collects_from_both_channels() {
+        let (tx1, rx1) = mpsc::channel();
+        let (tx2, rx2) = mpsc::channel();
+        let first = thread::spawn(move || {
+            for i in 0..50 {
+                tx1.send(i).unwrap();
+            }
+        });
+        let second = thread::spawn(move || {
+            for i in 50..80 {
+                tx2.send(i).unwrap();
+                thread::sleep(Duration::from_micros(100));
+            }
+        });
+        let mut drained = drain_two(rx1, rx2);
+        first.join().unwrap();
+        second.join().unwrap();
+        drained.sort();
+        assert_eq!(drained, (0..80).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn sync_channel_rejects_sends_when_full() {
+        let (tx, rx) = mpsc::sync_channel(2);
+        tx.try_send(1).unwrap();
+        tx.try_send(2).unwrap();
+        // send() would block here; try_send reports it instead
+        assert_eq!(tx.try_send(3), Err(mpsc::TrySendError::Full(3)));
+        assert_eq!(rx.recv(), Ok(1));
+        assert!(tx.try_send(3).is_ok());
+    }
+
+    #[test]
+    fn slow_consumer_makes_bounded_producer_wait() {
+        let delay = Duration::from_millis(20);
+        let (received, elapsed) = bounded_message_passing(1, 5, delay);
+        assert_eq!(received, vec![0, 1, 2, 3, 4]);
+        // One message can sit in the buffer and one in the consumer's hands,
+        // so the last three sends each wait for the consumer to finish one
+        assert!(elapsed >= delay * 3, "producer only took {:?}", elapsed);
+    }
+
+    #[test]
+    fn atomic_counter_counts_every_thread() {
+        assert_eq!(atomic_counter(10), 10);
+    }
+
+    #[test]
+    fn every_produced_item_is_consumed_once() {
+        for consumers in [1, 4] {
+            let mut consumed = producer_consumer(1000, consumers);
+            consumed.sort();
+            assert_eq!(consumed, (0..1000).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn format_sub_millisecond() {
+        assert_eq!(format_elapsed(Duration::from_micros(500)), "500µs");
+        assert_eq!(format_elapsed(Duration::from_nanos(999)), "0µs");
+    }
+
+    #[test]
+    fn format_milliseconds() {
+        assert_eq!(format_elapsed(Duration::from_millis(1)), "1ms");
+        assert_eq!(format_elapsed(Duration::from_micros(12_345)), "12ms");
+    }
+
+    #[test]
+    fn format_seconds() {
+        assert_eq!(format_elapsed(Duration::from_millis(1234)), "1.234s");
+        assert_eq!(format_elapsed(Duration::from_secs(90)), "90.000s");
+    }
+
+    #[test]
+    fn time_returns_result_and_records_duration() {
+        let res = time("time_returns_result_and_records_duration", || {
+            thread::sleep(Duration::from_millis(2));
+            42
+        });
+        assert_eq!(res, 42);
+        // other tests may record into TIMINGS concurrently, so look for ours
+        let timings = TIMINGS.lock().unwrap();
+        let (_, elapsed) = timings
+            .iter()
+            .find(|(label, _)| label == "time_returns_result_and_records_duration")
+            .expect("timing was not recorded");
+        assert!(*elapsed >= Duration::from_millis(2));
+    }
+
+    // Deterministic pseudo-random numbers (a linear congruential generator),
+    // so we don't need the rand crate just to shuffle test data
+    fn shuffled(n: usize) -> Vec<i64> {
+        let mut state: u64 = 12345;
+        (0..n)
+            .map(|_| {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+                (state >> 33) as i64 - (1 << 30)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parallel_merge_sort_matches_sort() {
+        for threads in [1, 2, 3, 8] {
+            let mut data = shuffled(10000);
+            let mut expected = data.clone();
+            expected.sort();
+            parallel_merge_sort(&mut data, threads);
+            assert_eq!(data, expected, "with {} threads", threads);
+        }
+    }
+
+    #[test]
+    fn parallel_merge_sort_trivial_inputs() {
+        let mut empty: Vec<i32> = vec![];
+        parallel_merge_sort(&mut empty, 4);
+        assert!(empty.is_empty());
+
+        let mut single = vec![42];
+        parallel_merge_sort(&mut single, 4);
+        assert_eq!(single, vec![42]);
+
+        let mut none = vec![3, 1, 2];
+        parallel_merge_sort(&mut none, 0); // no threads still sorts
+        assert_eq!(none, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn scoped_threading_matches_serial_sum() {
+        let data = shuffled(1001);
+        let expected: i64 = data.iter().sum();
+        for threads in [0, 1, 3, 8, 2000] {
+            assert_eq!(scoped_threading(&data, threads), expected);
+        }
+        assert_eq!(scoped_threading(&[], 4), 0);
+    }
+
+    #[test]
+    fn parallel_sum_matches_serial_sum() {
+        let data = shuffled(1001);
+        let expected: i64 = data.iter().sum();
+        for chunks in [0, 1, 3, 8, 2000] {
+            assert_eq!(parallel_sum(&data, chunks), expected);
+        }
+        assert_eq!(parallel_sum(&[], 4), 0);
+    }
+
+    #[test]
+    fn scheduler_runs_higher_priority_first() {
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let (release, gate) = mpsc::channel::<()>();
+        let scheduler = Scheduler::new(1);
+        // Occupy the only worker until everything else has been queued
+        scheduler.submit(
+            0,
+            Box::new(move || {
+                gate.recv().unwrap();
+            }),
+        );
+        for (priority, name) in [(1, "low"), (2, "medium"), (9, "high"), (2, "medium again")] {
+            let order = Arc::clone(&order);
+            scheduler.submit(priority, Box::new(move || order.lock().unwrap().push(name)));
+        }
+        release.send(()).unwrap();
+        drop(scheduler); // joins the worker once the queue is drained
+        assert_eq!(
+            *order.lock().unwrap(),
+            vec!["high", "medium", "medium again", "low"]
+        );
+    }
+
+    #[test]
+    fn default_thread_pool_runs_jobs() {
+        let pool = ThreadPool::default();
+        assert!(!pool.workers.is_empty());
+        let (tx, rx) = mpsc::channel();
+        for i in 0..10 {
+            let tx = tx.clone();
+            pool.execute(move || tx.send(i).unwrap());
+        }
+        drop(tx);
+        let mut results: Vec<i32> = rx.iter().collect();
+        results.sort();
+        assert_eq!(results, (0..10).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn dropping_thread_pool_finishes_queued_work() {
+        let done = Arc::new(Mutex::new(0));
+        let pool = ThreadPool::new(3);
+        for _ in 0..12 {
+            let done = Arc::clone(&done);
+            pool.execute(move || {
+                thread::sleep(Duration::from_millis(1));
+                *done.lock().unwrap() += 1;
+            });
+        }
+        // Drop joins every worker and would panic if any of them had
+        drop(pool);
+        assert_eq!(*done.lock().unwrap(), 12);
+    }
+
+    #[test]
+    #[should_panic(expected = "ThreadPool needs at least one thread")]
+    fn empty_thread_pool_is_rejected() {
+        ThreadPool::new(0);
+    }
 }
@@ -6,7 +6,7 @@
 // type and amount of data). Furthermore, the name of each enum variant becomes
 // a function that constructs an instance of the enum. The flexibility of this
 // enum system allows us to effectively define several related structs:
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 enum Message {
     Quit,
     Move { x: i32, y: i32 },
@@ -23,6 +23,7 @@ impl Message {
     }
 }
 
+#[derive(Debug, PartialEq)]
 enum Coin {
     Penny,
     Nickle,
@@ -44,6 +45,54 @@ fn value_in_cents(coin: Coin) -> i32 {
     }
 }
 
+// value_in_cents takes the coin by value (it moves the quarter's message out
+// to print it); cents only needs to look, so the coin can be kept afterwards
+impl Coin {
+    fn cents(&self) -> u32 {
+        match self {
+            Coin::Penny => 1,
+            Coin::Nickle => 5,
+            Coin::Dime => 10,
+            Coin::Quarter(_) => 25,
+        }
+    }
+}
+
+// Implementing FromStr is what lets us write "dime".parse::<Coin>(). A coin's
+// name doesn't say anything about the Message a quarter carries, so a parsed
+// quarter just gets Quit. Both spellings of nickel are accepted
+use std::str::FromStr;
+
+impl FromStr for Coin {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Coin, String> {
+        match s.to_lowercase().as_str() {
+            "penny" => Ok(Coin::Penny),
+            "nickel" | "nickle" => Ok(Coin::Nickle),
+            "dime" => Ok(Coin::Dime),
+            "quarter" => Ok(Coin::Quarter(Message::Quit)),
+            _ => Err(format!("Unrecognized coin '{}'", s)),
+        }
+    }
+}
+
+// Parses a space-separated pile like "dime penny quarter", returning its total
+// value in cents along with the coins. The first bad token stops the parse,
+// and the error says which (1-based) token it was
+fn parse_and_total(input: &str) -> Result<(u32, Vec<Coin>), String> {
+    let mut total = 0;
+    let mut coins = Vec::new();
+    for (i, token) in input.split_whitespace().enumerate() {
+        let coin: Coin = token
+            .parse()
+            .map_err(|e| format!("{} at position {}", e, i + 1))?;
+        total += coin.cents();
+        coins.push(coin);
+    }
+    Ok((total, coins))
+}
+
 fn plus_one(x: Option<i32>) -> Option<i32> {
     match x {
         None => None,
@@ -60,6 +109,213 @@ fn minus_one(x: Option<i32>) -> Option<i32> {
     }
 }
 
+// Enums, closures and trait objects together: a tiny event system. Each
+// variant of Event carries whatever data makes sense for that kind of event
+#[derive(Debug)]
+enum Event {
+    Click { x: i32, y: i32 },
+    KeyPress(char),
+    Resize { w: u32, h: u32 },
+}
+
+// Every closure has its own anonymous type, so to store different closures in
+// the same Vec we box them up as trait objects. Handlers only get a shared
+// reference to the event, so they can all see the same one
+type Handler = Box<dyn Fn(&Event)>;
+
+struct Dispatcher {
+    handlers: Vec<Handler>,
+}
+
+impl Dispatcher {
+    fn new() -> Dispatcher {
+        Dispatcher {
+            handlers: Vec::new(),
+        }
+    }
+
+    // 'static since the Dispatcher may outlive whatever scope the closure was
+    // created in, so it can't borrow from that scope
+    fn subscribe<F: Fn(&Event) + 'static>(&mut self, handler: F) {
+        self.handlers.push(Box::new(handler));
+    }
+
+    // Calls every handler, in the order they subscribed
+    fn dispatch(&self, event: &Event) {
+        for handler in &self.handlers {
+            handler(event);
+        }
+    }
+}
+
+// Enums can be recursive as long as the recursion goes through a pointer like
+// Box (otherwise the compiler couldn't work out how big an Expr is). This is
+// the same trick the Cons list uses, but now each node can have two children,
+// so an Expr is really a tree
+#[derive(Debug)]
+enum Expr {
+    Num(f64),
+    Add(Box<Expr>, Box<Expr>),
+    Sub(Box<Expr>, Box<Expr>),
+    Mul(Box<Expr>, Box<Expr>),
+    Div(Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    // Evaluates the tree bottom-up. The ? operator passes an error from either
+    // subtree straight up to the caller
+    fn eval(&self) -> Result<f64, String> {
+        match self {
+            Expr::Num(n) => Ok(*n),
+            Expr::Add(a, b) => Ok(a.eval()? + b.eval()?),
+            Expr::Sub(a, b) => Ok(a.eval()? - b.eval()?),
+            Expr::Mul(a, b) => Ok(a.eval()? * b.eval()?),
+            Expr::Div(a, b) => {
+                let divisor = b.eval()?;
+                if divisor == 0.0 {
+                    return Err(String::from("Division by zero"));
+                }
+                Ok(a.eval()? / divisor)
+            }
+        }
+    }
+}
+
+// To build an Expr from text like "(2 + 3) * 4", we first split the text
+// into tokens, then parse the tokens into a tree. Each token is an enum
+// variant; only numbers need to carry any data
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token {
+    Num(f64),
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    LParen,
+    RParen,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = input.char_indices().peekable();
+    while let Some(&(i, c)) = chars.peek() {
+        let token = match c {
+            '+' => Token::Plus,
+            '-' => Token::Minus,
+            '*' => Token::Star,
+            '/' => Token::Slash,
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            c if c.is_whitespace() => {
+                chars.next();
+                continue;
+            }
+            c if c.is_ascii_digit() || c == '.' => {
+                // consume the whole run of digits and decimal points
+                let mut end = i;
+                while let Some(&(j, d)) = chars.peek() {
+                    if !(d.is_ascii_digit() || d == '.') {
+                        break;
+                    }
+                    end = j + d.len_utf8();
+                    chars.next();
+                }
+                let text = &input[i..end];
+                let n = text
+                    .parse()
+                    .map_err(|_| format!("Invalid number '{}' at position {}", text, i))?;
+                tokens.push(Token::Num(n));
+                continue;
+            }
+            c => return Err(format!("Unexpected character '{}' at position {}", c, i)),
+        };
+        tokens.push(token);
+        chars.next();
+    }
+    Ok(tokens)
+}
+
+// A recursive descent parser, with one method per precedence level:
+//     expr   := term (('+' | '-') term)*
+//     term   := factor (('*' | '/') factor)*
+//     factor := number | '-' factor | '(' expr ')'
+// Since expr is built out of terms, * and / bind more tightly than + and -.
+// Looping (rather than recursing) on the operators makes them left
+// associative, so 8 - 2 - 1 is (8 - 2) - 1
+struct Parser {
+    tokens: std::iter::Peekable<std::vec::IntoIter<Token>>,
+}
+
+impl Parser {
+    fn expr(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.term()?;
+        loop {
+            lhs = match self.tokens.peek() {
+                Some(Token::Plus) => {
+                    self.tokens.next();
+                    Expr::Add(Box::new(lhs), Box::new(self.term()?))
+                }
+                Some(Token::Minus) => {
+                    self.tokens.next();
+                    Expr::Sub(Box::new(lhs), Box::new(self.term()?))
+                }
+                _ => return Ok(lhs),
+            };
+        }
+    }
+
+    fn term(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.factor()?;
+        loop {
+            lhs = match self.tokens.peek() {
+                Some(Token::Star) => {
+                    self.tokens.next();
+                    Expr::Mul(Box::new(lhs), Box::new(self.factor()?))
+                }
+                Some(Token::Slash) => {
+                    self.tokens.next();
+                    Expr::Div(Box::new(lhs), Box::new(self.factor()?))
+                }
+                _ => return Ok(lhs),
+            };
+        }
+    }
+
+    fn factor(&mut self) -> Result<Expr, String> {
+        match self.tokens.next() {
+            Some(Token::Num(n)) => Ok(Expr::Num(n)),
+            // there's no negation variant, so -x is parsed as 0 - x
+            Some(Token::Minus) => Ok(Expr::Sub(
+                Box::new(Expr::Num(0.0)),
+                Box::new(self.factor()?),
+            )),
+            Some(Token::LParen) => {
+                let inner = self.expr()?;
+                match self.tokens.next() {
+                    Some(Token::RParen) => Ok(inner),
+                    Some(token) => Err(format!("Expected ')' but found {:?}", token)),
+                    None => Err(String::from("Unmatched '('")),
+                }
+            }
+            Some(token) => Err(format!("Unexpected token {:?}", token)),
+            None => Err(String::from("Unexpected end of input")),
+        }
+    }
+}
+
+fn parse(input: &str) -> Result<Expr, String> {
+    let mut parser = Parser {
+        tokens: tokenize(input)?.into_iter().peekable(),
+    };
+    let expr = parser.expr()?;
+    // expr stops at the first token it can't use; anything left over is junk
+    match parser.tokens.next() {
+        None => Ok(expr),
+        Some(Token::RParen) => Err(String::from("Unmatched ')'")),
+        Some(token) => Err(format!("Unexpected token {:?}", token)),
+    }
+}
+
 fn main() {
     let msg = Message::Write(String::from("Hello, world!"));
     msg.call();
@@ -82,6 +338,10 @@ fn main() {
 
     let coin1 = Coin::Quarter(Message::Quit);
     println!("coin1: {:?}", value_in_cents(coin1));
+    match parse_and_total("quarter dime dime penny") {
+        Ok((cents, coins)) => println!("{} coins worth {} cents", coins.len(), cents),
+        Err(e) => println!("Couldn't count the pile: {}", e),
+    }
 
     // The if let control flow idiom is used when we want to take some action
     // conditionally on values with a particular match while ignoring all other
@@ -93,4 +353,171 @@ fn main() {
     } else {
         println!("Like the _ case in match")
     }
+
+    let mut dispatcher = Dispatcher::new();
+    dispatcher.subscribe(|event| println!("Got event: {:?}", event));
+    dispatcher.subscribe(|event| match event {
+        Event::Click { x, y } => println!("Clicked at ({}, {})", x, y),
+        Event::KeyPress(c) => println!("Pressed '{}'", c),
+        Event::Resize { w, h } => println!("Resized to {}x{}", w, h),
+    });
+    dispatcher.dispatch(&Event::Click { x: 3, y: 4 });
+    dispatcher.dispatch(&Event::KeyPress('q'));
+    dispatcher.dispatch(&Event::Resize { w: 800, h: 600 });
+
+    // (2 + 3) * 4
+    let expr = Expr::Mul(
+        Box::new(Expr::Add(
+            Box::new(Expr::Num(2.0)),
+            Box::new(Expr::Num(3.0)),
+        )),
+        Box::new(Expr::Num(4.0)),
+    );
+    println!("{:?} = {:?}", expr, expr.eval());
+    // 1 / (2 - 2)
+    let expr = Expr::Div(
+        Box::new(Expr::Num(1.0)),
+        Box::new(Expr::Sub(
+            Box::new(Expr::Num(2.0)),
+            Box::new(Expr::Num(2.0)),
+        )),
+    );
+    println!("{:?} = {:?}", expr, expr.eval());
+
+    for input in ["2 + 3 * 4", "(2 + 3) * 4", "-(1 - 4) / 2", "(2 + 3"] {
+        match parse(input) {
+            Ok(expr) => println!("{} = {:?}", input, expr.eval()),
+            Err(e) => println!("Couldn't parse {}: {}", input, e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    fn dispatch_calls_every_handler() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let mut dispatcher = Dispatcher::new();
+
+        let first = Rc::clone(&log);
+        dispatcher.subscribe(move |event| first.borrow_mut().push(format!("first: {:?}", event)));
+        let second = Rc::clone(&log);
+        dispatcher.subscribe(move |event| {
+            if let Event::KeyPress(c) = event {
+                second.borrow_mut().push(format!("second: {}", c));
+            }
+        });
+
+        assert!(log.borrow().is_empty()); // nothing fires until dispatch
+        dispatcher.dispatch(&Event::KeyPress('a'));
+        dispatcher.dispatch(&Event::Click { x: 1, y: 2 });
+        assert_eq!(
+            *log.borrow(),
+            vec![
+                "first: KeyPress('a')",
+                "second: a",
+                "first: Click { x: 1, y: 2 }",
+            ]
+        );
+    }
+
+    #[test]
+    fn eval_nested_expression() {
+        // (2 + 3) * 4
+        let expr = Expr::Mul(
+            Box::new(Expr::Add(
+                Box::new(Expr::Num(2.0)),
+                Box::new(Expr::Num(3.0)),
+            )),
+            Box::new(Expr::Num(4.0)),
+        );
+        assert_eq!(expr.eval(), Ok(20.0));
+        // 10 - 6 / 2
+        let expr = Expr::Sub(
+            Box::new(Expr::Num(10.0)),
+            Box::new(Expr::Div(
+                Box::new(Expr::Num(6.0)),
+                Box::new(Expr::Num(2.0)),
+            )),
+        );
+        assert_eq!(expr.eval(), Ok(7.0));
+    }
+
+    #[test]
+    fn eval_division_by_zero_is_an_error() {
+        // 1 / (2 - 2)
+        let expr = Expr::Div(
+            Box::new(Expr::Num(1.0)),
+            Box::new(Expr::Sub(
+                Box::new(Expr::Num(2.0)),
+                Box::new(Expr::Num(2.0)),
+            )),
+        );
+        assert_eq!(expr.eval(), Err(String::from("Division by zero")));
+    }
+
+    #[test]
+    fn tokenize_numbers_and_operators() {
+        assert_eq!(
+            tokenize("(1.5+2) *3"),
+            Ok(vec![
+                Token::LParen,
+                Token::Num(1.5),
+                Token::Plus,
+                Token::Num(2.0),
+                Token::RParen,
+                Token::Star,
+                Token::Num(3.0),
+            ])
+        );
+        assert!(tokenize("2 ^ 3").is_err());
+        assert!(tokenize("1.2.3").is_err());
+    }
+
+    #[test]
+    fn parse_respects_precedence() {
+        assert_eq!(parse("2 + 3 * 4").unwrap().eval(), Ok(14.0));
+        assert_eq!(parse("(2 + 3) * 4").unwrap().eval(), Ok(20.0));
+        assert_eq!(parse("8 - 2 - 1").unwrap().eval(), Ok(5.0));
+        assert_eq!(parse("-(1 - 4) / 2").unwrap().eval(), Ok(1.5));
+    }
+
+    #[test]
+    fn parse_malformed_input() {
+        assert_eq!(parse("(2 + 3").unwrap_err(), "Unmatched '('");
+        assert_eq!(parse("2 + 3)").unwrap_err(), "Unmatched ')'");
+        assert_eq!(parse("2 +").unwrap_err(), "Unexpected end of input");
+        assert_eq!(parse("2 * / 3").unwrap_err(), "Unexpected token Slash");
+        assert_eq!(parse("2 3").unwrap_err(), "Unexpected token Num(3.0)");
+        assert_eq!(parse("").unwrap_err(), "Unexpected end of input");
+    }
+
+    #[test]
+    fn parse_and_total_sums_a_pile() {
+        let (total, coins) = parse_and_total("quarter dime  Nickel penny penny").unwrap();
+        assert_eq!(total, 42);
+        assert_eq!(
+            coins,
+            vec![
+                Coin::Quarter(Message::Quit),
+                Coin::Dime,
+                Coin::Nickle,
+                Coin::Penny,
+                Coin::Penny,
+            ]
+        );
+        assert_eq!(parse_and_total(""), Ok((0, vec![])));
+    }
+
+    #[test]
+    fn parse_and_total_reports_bad_token_position() {
+        assert_eq!(
+            parse_and_total("dime loonie penny toonie"),
+            Err(String::from("Unrecognized coin 'loonie' at position 2"))
+        );
+    }
 }
@@ -27,8 +27,12 @@
 // this contract to be portable, in the sense that we don't have to worry about
 // making these checks manually every time (although the caller will need to
 // worry about this)
+use std::fmt;
 use std::fs::File;
 use std::io::{self, Read, ErrorKind};
+use std::num::ParseIntError;
+use std::thread;
+use std::time::Duration;
 
 fn demo_panic() {
     // Unrecoverable errors are dealt with using panic!
@@ -74,7 +78,7 @@ fn demo_file_open2() {
     let _f = File::open("hello.txt").expect("Failed to open hello.txt");
 }
 
-fn demo_err_prop() -> Result<String, io::Error> {
+fn read_file_contents(path: &str) -> Result<String, io::Error> {
     // Propagating errors to the caller is a good idea when we don't
     // necessarily know how the errors should be handled (i.e., it's more
     // appropriate for the caller to decide what to do).
@@ -89,21 +93,127 @@ fn demo_err_prop() -> Result<String, io::Error> {
     // operator can only (I think...) be used in functions that have a return
     // type of Result<T, E> or Option<T> or another type implementing
     // std::ops::Try
-    let mut f = File::open("hello.txt")?;
+    let mut f = File::open(path)?;
     let mut s = String::new();
     f.read_to_string(&mut s)?;
-    // File::open("hello.txt")?.read_to_string(&mut s)?; // alternative chain
+    // File::open(path)?.read_to_string(&mut s)?; // alternative chain
     Ok(s)
 }
 
+// When a function can fail in more than one way, we can define our own error
+// type with a variant per kind of failure. Implementing From for each
+// underlying error type is what lets ? convert them automatically (see the note
+// about from in read_file_contents), and implementing Display and Error makes
+// AppError behave like any other error, e.g. in a Box<dyn Error>
+#[derive(Debug)]
+enum AppError {
+    Io(io::Error),
+    Parse(ParseIntError),
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AppError::Io(e) => write!(f, "I/O error: {}", e),
+            AppError::Parse(e) => write!(f, "Parse error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for AppError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AppError::Io(e) => Some(e),
+            AppError::Parse(e) => Some(e),
+        }
+    }
+}
+
+impl From<io::Error> for AppError {
+    fn from(e: io::Error) -> AppError {
+        AppError::Io(e)
+    }
+}
+
+impl From<ParseIntError> for AppError {
+    fn from(e: ParseIntError) -> AppError {
+        AppError::Parse(e)
+    }
+}
+
+// Each ? below produces a different error type, and both end up as AppError
+fn read_first_number(path: &str) -> Result<i64, AppError> {
+    let mut s = String::new();
+    File::open(path)?.read_to_string(&mut s)?;
+    let first_line = s.lines().next().unwrap_or("");
+    Ok(first_line.trim().parse()?)
+}
+
+fn demo_custom_error() {
+    match read_first_number("number.txt") {
+        Ok(n) => println!("The first line of number.txt is {}", n),
+        Err(e) => println!("Couldn't read a number: {}", e),
+    }
+}
+
+// Some failures are transient (a file another process hasn't finished
+// writing, a flaky network share), so trying again after a pause can succeed.
+// Asking for zero attempts is a contract violation rather than an error: with
+// no attempt there's no error to hand back
+fn retry<T, E, F: FnMut() -> Result<T, E>>(
+    mut op: F,
+    attempts: u32,
+    delay: Duration,
+) -> Result<T, E> {
+    assert!(attempts > 0, "retry needs at least one attempt");
+    let mut tries = 1;
+    loop {
+        match op() {
+            Ok(val) => return Ok(val),
+            Err(e) if tries == attempts => return Err(e),
+            Err(_) => {
+                tries += 1;
+                thread::sleep(delay);
+            }
+        }
+    }
+}
+
+fn demo_retry() {
+    match retry(|| File::open("hello.txt"), 3, Duration::from_millis(500)) {
+        Ok(_) => println!("Opened hello.txt"),
+        Err(e) => println!("Gave up on hello.txt after 3 attempts: {}", e),
+    }
+}
+
+// The menu, in the order it's shown: option n runs DEMOS[n - 1]. Adding a
+// demo only takes a new line here. Plain fn pointers work as long as the
+// demo takes no arguments and returns nothing; a closure that doesn't capture
+// anything coerces to one, which is how read_file_contents fits in
+const DEMOS: [(&str, fn()); 7] = [
+    ("demo panic", demo_panic),
+    ("demo out of bounds heap access", demo_out_of_bounds),
+    ("file open demo", demo_file_open),
+    ("file open demo 2", demo_file_open2),
+    ("demo error prop", || {
+        let contents = read_file_contents("hello.txt").expect("Error opening file!");
+        println!("hello.txt contains: {}", contents);
+    }),
+    ("demo custom error type", demo_custom_error),
+    ("demo retrying a file open", demo_retry),
+];
+
+fn find_demo(choice: u32) -> Option<fn()> {
+    let index = (choice as usize).checked_sub(1)?;
+    DEMOS.get(index).map(|&(_, demo)| demo)
+}
+
 fn main() {
     loop {
-        println!("Enter a number between 1 and 4");
-        println!("\t1 => demo panic");
-        println!("\t2 => demo out of bounds heap access");
-        println!("\t3 => file open demo");
-        println!("\t4 => file open demo 2");
-        println!("\t5 => demo error prop");
+        println!("Enter a number between 1 and {}", DEMOS.len());
+        for (i, (description, _)) in DEMOS.iter().enumerate() {
+            println!("\t{} => {}", i + 1, description);
+        }
         let mut input = String::new();
         io::stdin()
             .read_line(&mut input)
@@ -113,31 +223,112 @@ fn main() {
             Err(_) => continue,
         };
 
-        match input {
-            1 => {
-                demo_panic();
-                break;
-            }
-            2 => {
-                demo_out_of_bounds();
-                break;
-            }
-            3 => {
-                demo_file_open();
-                break;
-            }
-            4 => {
-                demo_file_open2();
-                break;
-            }
-            5 => {
-                demo_err_prop().expect("Error opening file!");
+        match find_demo(input) {
+            Some(demo) => {
+                demo();
                 break;
             }
-            _ => {
+            None => {
                 println!("Unexpected value! Try again");
                 continue;
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+
+    // A file in the system temp dir, named per test so tests running in
+    // parallel don't trample each other
+    fn temp_file(name: &str, contents: &str) -> PathBuf {
+        let file_name = format!("error_handling_{}_{}", std::process::id(), name);
+        let path = std::env::temp_dir().join(file_name);
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn every_menu_option_has_a_demo() {
+        for choice in 1..=DEMOS.len() as u32 {
+            assert!(find_demo(choice).is_some(), "no demo for {}", choice);
+        }
+        assert!(find_demo(0).is_none());
+        assert!(find_demo(DEMOS.len() as u32 + 1).is_none());
+    }
+
+    #[test]
+    fn retry_succeeds_after_failures() {
+        let mut calls = 0;
+        let result = retry(
+            || {
+                calls += 1;
+                if calls < 3 {
+                    Err("not yet")
+                } else {
+                    Ok(calls)
+                }
+            },
+            5,
+            Duration::from_millis(1),
+        );
+        assert_eq!(result, Ok(3));
+    }
+
+    #[test]
+    fn retry_returns_last_error_when_exhausted() {
+        let mut calls = 0;
+        let result: Result<(), u32> = retry(
+            || {
+                calls += 1;
+                Err(calls)
+            },
+            4,
+            Duration::ZERO,
+        );
+        assert_eq!(result, Err(4));
+    }
+
+    #[test]
+    fn read_file_contents_returns_whole_file() {
+        let path = temp_file("contents", "Hello,\nworld!\n");
+        let result = read_file_contents(path.to_str().unwrap());
+        fs::remove_file(&path).unwrap();
+        assert_eq!(result.unwrap(), "Hello,\nworld!\n");
+    }
+
+    #[test]
+    fn read_file_contents_reports_missing_file() {
+        let err = read_file_contents("this/file/does/not/exist.txt").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn reads_number_from_first_line() {
+        let path = temp_file("number", " 42 \nnot a number\n");
+        let result = read_first_number(path.to_str().unwrap());
+        fs::remove_file(&path).unwrap();
+        assert_eq!(result.unwrap(), 42);
+    }
+
+    #[test]
+    fn missing_file_is_io_error() {
+        match read_first_number("this/file/does/not/exist.txt") {
+            Err(AppError::Io(e)) => assert_eq!(e.kind(), ErrorKind::NotFound),
+            other => panic!("expected an I/O error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn bad_number_is_parse_error() {
+        let path = temp_file("not_a_number", "forty-two\n");
+        let result = read_first_number(path.to_str().unwrap());
+        fs::remove_file(&path).unwrap();
+        let err = result.unwrap_err();
+        assert!(matches!(err, AppError::Parse(_)));
+        assert!(err.to_string().starts_with("Parse error: "));
+    }
+}
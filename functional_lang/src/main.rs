@@ -4,13 +4,18 @@
 // things that can be stored in variables and iterators are for processing
 // collections. Pattern matching and enums are influenced by this way of
 // thinking too.
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 // memoization/lazy evaluation pattern can be used alongside closures. Structs,
 // enums and functions that use closures are annotated with generics and trait
 // bounds.
+// The cache can optionally be bounded. Once it holds capacity values, making
+// room for a new one evicts the least recently used value. recency holds every
+// cached key, ordered from least to most recently used; an unbounded cache
+// never evicts anything, so it leaves recency empty and skips that upkeep
 struct Cacher<T, U, V>
 where
     U: std::hash::Hash + std::cmp::Eq,
@@ -18,31 +23,252 @@ where
 {
     calculation: T,
     value_map: HashMap<U, V>,
+    capacity: Option<usize>, // None means unbounded
+    recency: VecDeque<U>,
+    hits: u64,   // value() calls answered from the cache
+    misses: u64, // value() calls that had to run calculation
 }
 
+// Keys and values only need to be Clone (not Copy), so the cache works with
+// types like String and Vec too. The cache keeps its own copy of each key
+// and value, and hands the caller a clone
 impl<T, U, V> Cacher<T, U, V>
 where
-    U: std::hash::Hash + std::cmp::Eq + Copy,
-    V: Copy,
+    U: std::hash::Hash + std::cmp::Eq + Clone,
+    V: Clone,
     T: Fn(U) -> V,
 {
     fn new(calculation: T) -> Cacher<T, U, V> {
         Cacher {
             calculation,
             value_map: HashMap::new(),
+            capacity: None,
+            recency: VecDeque::new(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    // A capacity of 0 means nothing is ever cached
+    fn with_capacity(calculation: T, capacity: usize) -> Cacher<T, U, V> {
+        Cacher {
+            capacity: Some(capacity),
+            ..Cacher::new(calculation)
         }
     }
 
     fn value(&mut self, arg: U) -> V {
         match self.value_map.get(&arg) {
-            Some(v) => *v,
+            Some(v) => {
+                let v = v.clone();
+                self.hits += 1;
+                if self.capacity.is_some() {
+                    self.touch(arg);
+                }
+                v
+            }
             None => {
-                let v = (self.calculation)(arg);
-                self.value_map.insert(arg, v);
+                self.misses += 1;
+                let v = (self.calculation)(arg.clone());
+                match self.capacity {
+                    None => {
+                        self.value_map.insert(arg, v.clone());
+                    }
+                    Some(0) => (),
+                    Some(capacity) => {
+                        if self.value_map.len() >= capacity {
+                            if let Some(oldest) = self.recency.pop_front() {
+                                self.value_map.remove(&oldest);
+                            }
+                        }
+                        self.value_map.insert(arg.clone(), v.clone());
+                        self.recency.push_back(arg);
+                    }
+                }
                 v
             }
         }
     }
+
+    // Peeks at the cache without ever running calculation. It takes &self, so
+    // it doesn't count as a hit or a miss and doesn't change the LRU order
+    fn get_if_present(&self, arg: &U) -> Option<V> {
+        self.value_map.get(arg).cloned()
+    }
+
+    // Marks arg as the most recently used key. Finding it is a linear scan,
+    // which is fine for small caches; a real LRU cache would pair the map
+    // with a linked list to make this O(1)
+    fn touch(&mut self, arg: U) {
+        if let Some(pos) = self.recency.iter().position(|k| *k == arg) {
+            self.recency.remove(pos);
+        }
+        self.recency.push_back(arg);
+    }
+}
+
+// Methods that never hand out a cached value don't need to clone one, so they
+// go in their own impl block without the Clone bounds
+impl<T, U, V> Cacher<T, U, V>
+where
+    U: std::hash::Hash + std::cmp::Eq,
+    T: Fn(U) -> V,
+{
+    // Forgets every cached value
+    fn clear(&mut self) {
+        self.value_map.clear();
+        self.recency.clear();
+    }
+
+    // Forgets the value cached for arg, returning whether there was one
+    fn invalidate(&mut self, arg: &U) -> bool {
+        self.recency.retain(|k| k != arg);
+        self.value_map.remove(arg).is_some()
+    }
+
+    // (hits, misses) so far
+    fn stats(&self) -> (u64, u64) {
+        (self.hits, self.misses)
+    }
+
+    // Fraction of value() calls that were answered from the cache, or 0.0 if
+    // there haven't been any
+    fn hit_ratio(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64
+        }
+    }
+}
+
+// Cacher::value needs &mut self, so a Cacher can't be shared between threads
+// (an Arc only hands out shared references). SyncCacher moves the mutability
+// inside: the map lives behind a Mutex, so value() only needs &self and the
+// whole thing can go in an Arc. The lock is held while calculation runs, which
+// serializes misses but guarantees each key is only ever computed once, even
+// when several threads ask for it at the same time
+struct SyncCacher<T, U, V>
+where
+    U: std::hash::Hash + std::cmp::Eq,
+    T: Fn(U) -> V + Send + Sync,
+{
+    calculation: T,
+    value_map: Mutex<HashMap<U, V>>,
+}
+
+impl<T, U, V> SyncCacher<T, U, V>
+where
+    U: std::hash::Hash + std::cmp::Eq + Clone,
+    V: Clone,
+    T: Fn(U) -> V + Send + Sync,
+{
+    fn new(calculation: T) -> SyncCacher<T, U, V> {
+        SyncCacher {
+            calculation,
+            value_map: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn value(&self, arg: U) -> V {
+        let mut map = self.value_map.lock().unwrap();
+        if let Some(v) = map.get(&arg) {
+            return v.clone();
+        }
+        let v = (self.calculation)(arg.clone());
+        map.insert(arg, v.clone());
+        v
+    }
+}
+
+// Wraps a closure so that calling it again too soon does nothing: it only runs
+// if at least interval has passed since the last time it actually ran (the
+// very first call always runs). F is FnMut so the wrapped closure is free to
+// mutate whatever it captured, which means call needs &mut self too
+struct Debounced<F: FnMut()> {
+    f: F,
+    interval: Duration,
+    last_run: Option<Instant>,
+}
+
+impl<F: FnMut()> Debounced<F> {
+    fn new(interval: Duration, f: F) -> Debounced<F> {
+        Debounced {
+            f,
+            interval,
+            last_run: None,
+        }
+    }
+
+    // Returns whether the closure ran
+    fn call(&mut self) -> bool {
+        let now = Instant::now();
+        if let Some(last) = self.last_run {
+            if now.duration_since(last) < self.interval {
+                return false;
+            }
+        }
+        self.last_run = Some(now);
+        (self.f)();
+        true
+    }
+}
+
+// A cache that forgets entries for two reasons: it only holds capacity of
+// them, evicting the least recently used one (the same recency queue Cacher
+// uses) to make room, and each entry expires ttl after it was inserted.
+// Expiry is lazy: nothing runs in the background, a stale entry is just
+// dropped the next time someone asks for it. Reading an entry makes it the
+// most recently used one but doesn't extend its lifetime; inserting the key
+// again does
+struct TimedLru<K, V> {
+    capacity: usize,
+    ttl: Duration,
+    entries: HashMap<K, (V, Instant)>, // value and when it was inserted
+    recency: VecDeque<K>,              // least to most recently used
+}
+
+impl<K, V> TimedLru<K, V>
+where
+    K: std::hash::Hash + std::cmp::Eq + Clone,
+    V: Clone,
+{
+    fn new(capacity: usize, ttl: Duration) -> TimedLru<K, V> {
+        TimedLru {
+            capacity,
+            ttl,
+            entries: HashMap::new(),
+            recency: VecDeque::new(),
+        }
+    }
+
+    fn insert(&mut self, key: K, value: V) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.contains_key(&key) {
+            self.recency.retain(|k| *k != key);
+        } else if self.entries.len() >= self.capacity {
+            if let Some(oldest) = self.recency.pop_front() {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.entries.insert(key.clone(), (value, Instant::now()));
+        self.recency.push_back(key);
+    }
+
+    // Like Cacher, hands back a clone of the cached value
+    fn get(&mut self, key: &K) -> Option<V> {
+        let inserted = self.entries.get(key)?.1;
+        self.recency.retain(|k| k != key);
+        if inserted.elapsed() >= self.ttl {
+            self.entries.remove(key);
+            return None;
+        }
+        self.recency.push_back(key.clone());
+        self.entries.get(key).map(|(value, _)| value.clone())
+    }
 }
 
 fn generate_workout(intensity: u32, random_number: u32) {
@@ -74,13 +300,46 @@ fn generate_workout(intensity: u32, random_number: u32) {
     }
 }
 
+// The three closure traits, from most to least restrictive for the closure
+// (and least to most restrictive for the caller):
+// - Fn: only reads what it captured, so it can be called any number of times,
+//   even through a shared reference
+// - FnMut: may change what it captured, so calling it needs &mut access, but
+//   it can still be called repeatedly
+// - FnOnce: may move a captured value out of itself, so it can only be
+//   called once
+// Every Fn is also an FnMut, and every FnMut is also an FnOnce.
+fn call_twice<T>(f: impl Fn() -> T) -> (T, T) {
+    (f(), f())
+}
+
+// f has to be declared mut, since each call can change its captured state
+fn call_n_times(n: usize, mut f: impl FnMut()) {
+    for _ in 0..n {
+        f();
+    }
+}
+
+fn call_once(f: impl FnOnce() -> String) -> String {
+    f()
+    // f(); // error: use of moved value: `f`
+}
+
+// count and limit act as front and back cursors: everything in count+1..=limit
+// is still to come. next() moves count up, next_back() moves limit down, and
+// the iterator is done once they meet, whichever end was used
 struct Counter {
-    count: u32,
+    count: u32, // last value yielded from the front
+    limit: u32, // next value to yield from the back
 }
 
 impl Counter {
     fn new() -> Counter {
-        Counter { count: 0 }
+        Counter::with_limit(5)
+    }
+
+    fn with_limit(limit: u32) -> Counter {
+        Counter { count: 0, limit }
     }
 }
 
@@ -88,7 +347,7 @@ impl Iterator for Counter {
     type Item = u32; // Needed for iterator trait, "associated type"
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.count < 5 {
+        if self.count < self.limit {
             self.count += 1;
             Some(self.count)
         } else {
@@ -97,8 +356,183 @@ impl Iterator for Counter {
     }
 }
 
+impl DoubleEndedIterator for Counter {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.count < self.limit {
+            self.limit -= 1;
+            Some(self.limit + 1)
+        } else {
+            None
+        }
+    }
+}
+
+// A more general counter: yields current, current + step, ... for as long as
+// the value stays below end (like (current..end).step_by(step)). A step that
+// isn't positive would never reach end, so such a counter yields nothing
+// rather than looping forever
+struct RangeCounter {
+    current: i64,
+    end: i64,
+    step: i64,
+}
+
+impl RangeCounter {
+    fn new(current: i64, end: i64, step: i64) -> RangeCounter {
+        RangeCounter { current, end, step }
+    }
+}
+
+impl Iterator for RangeCounter {
+    type Item = i64;
+
+    fn next(&mut self) -> Option<i64> {
+        if self.step <= 0 || self.current >= self.end {
+            return None;
+        }
+        let value = self.current;
+        // saturating so a step that overshoots i64::MAX just ends the range
+        self.current = self.current.saturating_add(self.step);
+        Some(value)
+    }
+}
+
+// Alternates elements from two iterators, continuing with the remainder of
+// whichever one is longer once the other is exhausted. iter::from_fn builds an
+// iterator out of a closure; here the closure captures (and mutates) a flag
+// recording which side to pull from next, so it is an FnMut
+fn interleave<I, J, T>(mut a: I, mut b: J) -> impl Iterator<Item = T>
+where
+    I: Iterator<Item = T>,
+    J: Iterator<Item = T>,
+{
+    let mut from_a = true;
+    std::iter::from_fn(move || {
+        let next = if from_a {
+            a.next().or_else(|| b.next())
+        } else {
+            b.next().or_else(|| a.next())
+        };
+        from_a = !from_a;
+        next
+    })
+}
+
+// Samples an iterator: keeps the first item and then every every-th one after
+// it, i.e. the items at positions 0, every, 2 * every, ... (so over 0..10 with
+// every = 3 it keeps 0, 3, 6 and 9, and every = 1 keeps everything). That's
+// the same selection step_by makes; this version just spells out the counting
+// with enumerate and filter. Panics if every is 0, since there'd be no next
+// item to keep
+fn throttle<I: Iterator>(iter: I, every: usize) -> impl Iterator<Item = I::Item> {
+    assert!(every > 0, "every must be non-zero");
+    iter.enumerate()
+        .filter(move |(i, _)| i % every == 0)
+        .map(|(_, item)| item)
+}
+
+// Applies f pairwise to the elements of a and b, stopping at the end of the
+// shorter vector (zip stops as soon as either side returns None)
+fn zip_with<A, B, C, F: Fn(A, B) -> C>(a: Vec<A>, b: Vec<B>, f: F) -> Vec<C> {
+    a.into_iter().zip(b).map(|(x, y)| f(x, y)).collect()
+}
+
+// Cumulative sums: [1, 2, 3] gives [1, 3, 6]. scan is like fold, except it
+// yields a value at every step instead of only at the end. The closure gets
+// &mut access to the running state, and returning Some(x) emits x (None would
+// end the iteration early)
+fn running_sum(nums: &[i64]) -> Vec<i64> {
+    nums.iter()
+        .scan(0, |total, &n| {
+            *total += n;
+            Some(*total)
+        })
+        .collect()
+}
+
 fn main() {
     generate_workout(24, 7);
+
+    let merged: Vec<u32> = interleave(Counter::new(), Counter::with_limit(7).skip(3)).collect();
+    // only keeps the 2 most recently used squares around
+    let mut squares = Cacher::with_capacity(|n: u32| n * n, 2);
+    for n in [2, 3, 2, 4] {
+        println!("{} squared is {}", n, squares.value(n));
+    }
+    let (hits, misses) = squares.stats();
+    println!(
+        "{} hits, {} misses, hit ratio {:.2}",
+        hits,
+        misses,
+        squares.hit_ratio()
+    );
+    if squares.get_if_present(&3).is_none() {
+        println!("3 squared was evicted");
+    }
+    if squares.invalidate(&4) {
+        println!("Forgot 4 squared");
+    }
+    squares.clear();
+
+    let mut sessions = TimedLru::new(2, Duration::from_secs(60));
+    sessions.insert("alice", 1);
+    sessions.insert("bob", 2);
+    sessions.get(&"alice");
+    sessions.insert("carol", 3); // evicts bob, the least recently used
+    println!(
+        "bob's session: {:?}, alice's session: {:?}",
+        sessions.get(&"bob"),
+        sessions.get(&"alice")
+    );
+
+    let cubes = Arc::new(SyncCacher::new(|n: u64| n * n * n));
+    let handles: Vec<_> = (1..=3)
+        .map(|n| {
+            let cubes = Arc::clone(&cubes);
+            thread::spawn(move || cubes.value(n))
+        })
+        .collect();
+    for handle in handles {
+        println!(
+            "Cube computed on another thread: {}",
+            handle.join().unwrap()
+        );
+    }
+
+    let mut saves = 0;
+    let mut autosave = Debounced::new(Duration::from_millis(100), || saves += 1);
+    for _ in 0..3 {
+        autosave.call(); // only the first of these rapid calls saves
+    }
+    println!("Saved {} time(s)", saves);
+
+    println!("Interleaved counters: {:?}", merged);
+    println!(
+        "Every other interleaved value: {:?}",
+        throttle(merged.iter(), 2).collect::<Vec<_>>()
+    );
+    println!(
+        "Odd numbers below 10: {:?}",
+        RangeCounter::new(1, 10, 2).collect::<Vec<_>>()
+    );
+    println!(
+        "Counting down: {:?}",
+        Counter::new().rev().collect::<Vec<_>>()
+    );
+    println!("Running sum: {:?}", running_sum(&[5, -2, 10, 1]));
+
+    let base = 10;
+    println!("Fn, called twice: {:?}", call_twice(|| base * 2));
+    let mut ticks = 0;
+    call_n_times(3, || ticks += 1);
+    println!("FnMut ticked {} times", ticks);
+    let name = String::from("Ferris");
+    // name is moved into the closure, and then out of it when it's called
+    println!("FnOnce returned {}", call_once(move || name));
+    println!(
+        "Pairwise products: {:?}",
+        zip_with(Counter::new().collect(), merged, |a, b| a * b)
+    );
 }
 
 #[test]
@@ -132,6 +566,145 @@ fn call_with_str() {
     assert_eq!(v1, 12);
 }
 
+#[test]
+fn lru_evicts_oldest_key_past_capacity() {
+    let calls = std::cell::Cell::new(0); // lets the Fn closure count calls
+    let mut c = Cacher::with_capacity(
+        |a: i32| {
+            calls.set(calls.get() + 1);
+            a * 10
+        },
+        2,
+    );
+
+    assert_eq!(c.value(1), 10);
+    assert_eq!(c.value(2), 20);
+    assert_eq!(c.value(3), 30); // evicts 1
+    assert_eq!(calls.get(), 3);
+    assert_eq!(c.value(3), 30); // still cached
+    assert_eq!(calls.get(), 3);
+    assert_eq!(c.value(1), 10); // recomputed, evicts 2
+    assert_eq!(calls.get(), 4);
+}
+
+#[test]
+fn lru_keeps_recently_used_key() {
+    let calls = std::cell::Cell::new(0);
+    let mut c = Cacher::with_capacity(
+        |a: i32| {
+            calls.set(calls.get() + 1);
+            a
+        },
+        2,
+    );
+
+    c.value(1);
+    c.value(2);
+    c.value(1); // 1 is now more recently used than 2
+    c.value(3); // so 2 is evicted
+    assert_eq!(calls.get(), 3);
+    c.value(1);
+    assert_eq!(calls.get(), 3);
+    c.value(2);
+    assert_eq!(calls.get(), 4);
+}
+
+#[test]
+fn unbounded_cacher_skips_recency_bookkeeping() {
+    let mut c = Cacher::new(|a: u32| a + 1);
+    for n in [1, 2, 1, 1] {
+        c.value(n);
+    }
+    assert_eq!(c.stats(), (2, 2));
+    assert!(c.recency.is_empty());
+}
+
+#[test]
+fn zero_capacity_caches_nothing() {
+    let calls = std::cell::Cell::new(0);
+    let mut c = Cacher::with_capacity(
+        |a: i32| {
+            calls.set(calls.get() + 1);
+            a
+        },
+        0,
+    );
+    c.value(1);
+    c.value(1);
+    assert_eq!(calls.get(), 2);
+}
+
+#[test]
+fn invalidate_forces_recompute() {
+    let calls = std::cell::Cell::new(0);
+    let mut c = Cacher::new(|a: i32| {
+        calls.set(calls.get() + 1);
+        a
+    });
+
+    c.value(1);
+    c.value(2);
+    assert!(c.invalidate(&1));
+    assert!(!c.invalidate(&1)); // already gone
+    assert!(!c.invalidate(&3)); // never cached
+    c.value(1); // recomputed
+    c.value(2); // still cached
+    assert_eq!(calls.get(), 3);
+}
+
+#[test]
+fn clear_forces_recompute() {
+    let calls = std::cell::Cell::new(0);
+    let mut c = Cacher::with_capacity(
+        |a: i32| {
+            calls.set(calls.get() + 1);
+            a
+        },
+        2,
+    );
+
+    c.value(1);
+    c.value(2);
+    c.clear();
+    c.value(1);
+    c.value(2);
+    assert_eq!(calls.get(), 4);
+    c.value(3); // evicts 1, which proves recency was reset along with the map
+    c.value(2);
+    assert_eq!(calls.get(), 5);
+}
+
+#[test]
+fn stats_count_hits_and_misses() {
+    let mut c = Cacher::new(|a: i32| a);
+    assert_eq!(c.stats(), (0, 0));
+    assert_eq!(c.hit_ratio(), 0.0);
+
+    c.value(7); // miss
+    c.value(7); // hit
+    assert_eq!(c.stats(), (1, 1));
+    assert_eq!(c.hit_ratio(), 0.5);
+
+    c.value(7);
+    c.value(7);
+    assert_eq!(c.stats(), (3, 1));
+    assert_eq!(c.hit_ratio(), 0.75);
+}
+
+#[test]
+fn cache_owned_string_values() {
+    let calls = std::cell::Cell::new(0);
+    let mut c = Cacher::new(|name: String| {
+        calls.set(calls.get() + 1);
+        format!("Hello, {}!", name)
+    });
+
+    assert_eq!(c.value(String::from("Ferris")), "Hello, Ferris!");
+    assert_eq!(c.value(String::from("Ferris")), "Hello, Ferris!");
+    assert_eq!(c.value(String::from("world")), "Hello, world!");
+    assert_eq!(calls.get(), 2);
+}
+
 #[test]
 fn iterator_demo() {
     let v1 = vec![4, 5, 6];
@@ -169,4 +742,201 @@ fn iterator_trait_methods_on_counter() {
         .filter(|x| x % 2 == 0)
         .sum();
     assert_eq!(sum, 10);
-}
\ No newline at end of file
+}
+
+#[test]
+fn interleave_alternates_then_drains_longer() {
+    let v: Vec<i32> = interleave(vec![1, 3, 5].into_iter(), vec![2, 4].into_iter()).collect();
+    assert_eq!(v, vec![1, 2, 3, 4, 5]);
+}
+
+#[test]
+fn interleave_with_empty() {
+    let v: Vec<i32> = interleave(std::iter::empty(), vec![1, 2].into_iter()).collect();
+    assert_eq!(v, vec![1, 2]);
+    let v: Vec<i32> = interleave(vec![1, 2].into_iter(), std::iter::empty()).collect();
+    assert_eq!(v, vec![1, 2]);
+}
+
+#[test]
+fn zip_with_adds_elementwise() {
+    let sums = zip_with(vec![1, 2, 3], vec![10, 20, 30], |a, b| a + b);
+    assert_eq!(sums, vec![11, 22, 33]);
+}
+
+#[test]
+fn zip_with_truncates_to_shorter() {
+    let pairs = zip_with(vec![1, 2, 3, 4], vec!["a", "b"], |n, s| {
+        format!("{}{}", s, n)
+    });
+    assert_eq!(pairs, vec!["a1", "b2"]);
+    let pairs = zip_with(vec![1], vec![2, 3, 4], |a, b| a * b);
+    assert_eq!(pairs, vec![2]);
+}
+
+#[test]
+fn sync_cacher_computes_each_key_once_across_threads() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counted = Arc::clone(&calls);
+    let cacher = Arc::new(SyncCacher::new(move |n: u32| {
+        counted.fetch_add(1, Ordering::SeqCst);
+        n * 10
+    }));
+
+    let handles: Vec<_> = (0..8)
+        .map(|i| {
+            let cacher = Arc::clone(&cacher);
+            thread::spawn(move || {
+                for key in 0..4 {
+                    assert_eq!(cacher.value((key + i) % 4), ((key + i) % 4) * 10);
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    assert_eq!(calls.load(Ordering::SeqCst), 4);
+}
+
+#[test]
+fn get_if_present_never_computes() {
+    let mut c = Cacher::new(|a: u32| a + 1);
+    assert_eq!(c.get_if_present(&1), None);
+    assert_eq!(c.stats(), (0, 0));
+    c.value(1);
+    assert_eq!(c.get_if_present(&1), Some(2));
+    assert_eq!(c.stats(), (0, 1));
+}
+
+#[test]
+fn counter_with_limit() {
+    let mut counter = Counter::with_limit(3);
+    assert_eq!(counter.next(), Some(1));
+    assert_eq!(counter.next(), Some(2));
+    assert_eq!(counter.next(), Some(3));
+    assert_eq!(counter.next(), None);
+    assert_eq!(Counter::new().collect::<Vec<_>>(), [1, 2, 3, 4, 5]);
+}
+
+#[test]
+fn counter_counts_down() {
+    assert_eq!(Counter::new().rev().collect::<Vec<_>>(), [5, 4, 3, 2, 1]);
+
+    // the two ends meet in the middle without repeating a value
+    let mut counter = Counter::new();
+    assert_eq!(counter.next(), Some(1));
+    assert_eq!(counter.next_back(), Some(5));
+    assert_eq!(counter.next_back(), Some(4));
+    assert_eq!(counter.next(), Some(2));
+    assert_eq!(counter.next(), Some(3));
+    assert_eq!(counter.next(), None);
+    assert_eq!(counter.next_back(), None);
+}
+
+#[test]
+fn range_counter_steps_up_to_end() {
+    assert_eq!(
+        RangeCounter::new(0, 10, 3).collect::<Vec<_>>(),
+        [0, 3, 6, 9]
+    );
+    assert_eq!(RangeCounter::new(-4, 2, 2).collect::<Vec<_>>(), [-4, -2, 0]);
+    assert_eq!(RangeCounter::new(5, 5, 1).next(), None);
+    assert_eq!(RangeCounter::new(i64::MAX - 1, i64::MAX, 5).count(), 1);
+}
+
+#[test]
+fn range_counter_rejects_non_positive_steps() {
+    assert_eq!(RangeCounter::new(0, 10, 0).next(), None);
+    assert_eq!(RangeCounter::new(0, 10, -1).next(), None);
+}
+
+#[test]
+fn debounced_suppresses_rapid_calls() {
+    use std::cell::Cell;
+
+    let calls = Cell::new(0);
+    let mut debounced = Debounced::new(Duration::from_millis(50), || calls.set(calls.get() + 1));
+    assert!(debounced.call());
+    for _ in 0..5 {
+        assert!(!debounced.call());
+    }
+    assert_eq!(calls.get(), 1);
+
+    thread::sleep(Duration::from_millis(60));
+    assert!(debounced.call());
+    assert_eq!(calls.get(), 2);
+}
+
+#[test]
+fn throttle_keeps_first_then_every_nth() {
+    assert_eq!(throttle(0..10, 3).collect::<Vec<_>>(), [0, 3, 6, 9]);
+    assert_eq!(
+        throttle(0..10, 1).collect::<Vec<_>>(),
+        (0..10).collect::<Vec<_>>()
+    );
+    assert_eq!(throttle(0..2, 5).collect::<Vec<_>>(), [0]);
+}
+
+#[test]
+fn timed_lru_evicts_least_recently_used() {
+    let mut cache = TimedLru::new(2, Duration::from_secs(60));
+    cache.insert(1, "one");
+    cache.insert(2, "two");
+    assert_eq!(cache.get(&1), Some("one")); // 2 is now least recently used
+    cache.insert(3, "three");
+    assert_eq!(cache.get(&2), None);
+    assert_eq!(cache.get(&1), Some("one"));
+    assert_eq!(cache.get(&3), Some("three"));
+}
+
+#[test]
+fn timed_lru_expires_stale_entries() {
+    let mut cache = TimedLru::new(4, Duration::from_millis(20));
+    cache.insert("stale", 1);
+    thread::sleep(Duration::from_millis(30));
+    cache.insert("fresh", 2);
+    assert_eq!(cache.get(&"stale"), None);
+    assert_eq!(cache.get(&"fresh"), Some(2));
+    // the expired entry no longer takes up room
+    assert_eq!(cache.entries.len(), 1);
+    assert_eq!(cache.recency, ["fresh"]);
+}
+
+#[test]
+fn running_sum_accumulates() {
+    assert_eq!(running_sum(&[1, 2, 3]), vec![1, 3, 6]);
+    assert_eq!(running_sum(&[4, -4, 1]), vec![4, 0, 1]);
+    assert!(running_sum(&[]).is_empty());
+}
+
+#[test]
+fn fn_closure_only_reads_captures() {
+    let greeting = String::from("hi");
+    let (first, second) = call_twice(|| greeting.len());
+    assert_eq!((first, second), (2, 2));
+    assert_eq!(greeting, "hi"); // still usable, it was only borrowed
+}
+
+#[test]
+fn fn_mut_closure_accumulates() {
+    let mut counter = 0;
+    let mut seen = Vec::new();
+    call_n_times(4, || {
+        counter += 1;
+        seen.push(counter);
+    });
+    assert_eq!(counter, 4);
+    assert_eq!(seen, vec![1, 2, 3, 4]);
+}
+
+#[test]
+fn fn_once_closure_consumes_capture() {
+    let phrase = String::from("closures rock");
+    // + takes phrase by value, moving it out of the closure, so this closure
+    // only implements FnOnce and call_twice wouldn't accept it
+    let sentence = call_once(move || phrase + "!");
+    assert_eq!(sentence, "closures rock!");
+}
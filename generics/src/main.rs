@@ -27,29 +27,105 @@
 // { ... }
 // In summary, traits let us specify to the compiler circumstances under which
 // a generic type must have a certain set of behaviours.
-fn find_max<T: PartialOrd + Copy>(list: &[T]) -> T {
-    let mut max = list[0];
+// An empty slice has no maximum, so rather than panicking on list[0] we return
+// an Option and let the caller decide what to do about it. The ? operator
+// works on Options too: first() returns None for an empty slice, and ? returns
+// that None from find_max straight away
+fn find_max<T: PartialOrd + Copy>(list: &[T]) -> Option<T> {
+    let mut max = *list.first()?;
     for &item in list {
         if item > max {
             // T needs to have PartialOrd trait
             max = item; // T needs to have Copy trait
         }
     }
-    max
+    Some(max)
 }
 
-struct Point<T> {
+fn find_min<T: PartialOrd + Copy>(list: &[T]) -> Option<T> {
+    let mut min = *list.first()?;
+    for &item in list {
+        if item < min {
+            min = item;
+        }
+    }
+    Some(min)
+}
+
+// Calling find_min and then find_max walks the slice twice and does about 2n
+// comparisons. Processing the elements in pairs gets that down to about 1.5n:
+// compare the two elements of a pair with each other first, then only the
+// smaller one can be a new min and only the larger one can be a new max
+fn find_min_max<T: PartialOrd + Copy>(list: &[T]) -> Option<(T, T)> {
+    if list.is_empty() {
+        return None;
+    }
+    // Seed with the first element if the length is odd, or the first pair if
+    // it's even, so that what's left always splits evenly into pairs
+    let (mut min, mut max, rest) = if list.len() % 2 == 1 {
+        (list[0], list[0], &list[1..])
+    } else if list[0] < list[1] {
+        (list[0], list[1], &list[2..])
+    } else {
+        (list[1], list[0], &list[2..])
+    };
+    for pair in rest.chunks_exact(2) {
+        let (small, big) = if pair[0] < pair[1] {
+            (pair[0], pair[1])
+        } else {
+            (pair[1], pair[0])
+        };
+        if small < min {
+            min = small;
+        }
+        if big > max {
+            max = big;
+        }
+    }
+    Some((min, max))
+}
+
+// x and y may have different types. The "= T" is a default type parameter:
+// if U isn't given, it's the same as T, so Point<f32> is shorthand for
+// Point<f32, f32>
+#[derive(Debug, PartialEq)]
+struct Point<T, U = T> {
     x: T,
-    y: T,
+    y: U,
 }
 
-// Function available for all generic types T. Note that here, type T
-// parametrizes the impl block, while it is possible for the methods within the
-// block to have their own distinct parameters
-impl<T> Point<T> {
+// Function available for all generic types T and U. Note that here, types T
+// and U parametrize the impl block, while it is possible for the methods
+// within the block to have their own distinct parameters (like V and W in
+// mixup, which only matter for that one method)
+impl<T, U> Point<T, U> {
     fn x(&self) -> &T {
         &self.x
     }
+
+    // Takes x from this point and y from the other one. Both points are taken
+    // by value, so their fields are moved into the new Point
+    fn mixup<V, W>(self, other: Point<V, W>) -> Point<T, W> {
+        Point {
+            x: self.x,
+            y: other.y,
+        }
+    }
+}
+
+// Operator overloading: a + b is sugar for std::ops::Add::add(a, b), so
+// implementing Add lets us use + on Points. It's only implemented when the
+// coordinates themselves can be added, and like the built-in arithmetic
+// operators, it takes both operands by value
+impl<T: std::ops::Add<Output = T>> std::ops::Add for Point<T> {
+    type Output = Point<T>;
+
+    fn add(self, other: Point<T>) -> Point<T> {
+        Point {
+            x: self.x + other.x,
+            y: self.y + other.y,
+        }
+    }
 }
 
 // Conditionally implements the cmp_display method, with this depending on the
@@ -73,6 +149,10 @@ impl Point<f32> {
     fn dist_from_origin(&self) -> f32 {
         (self.x.powi(2) + self.y.powi(2)).sqrt()
     }
+
+    fn distance_to(&self, other: &Point<f32>) -> f32 {
+        ((self.x - other.x).powi(2) + (self.y - other.y).powi(2)).sqrt()
+    }
 }
 
 // Traits define shared behaviour across types in an abstract way. By
@@ -92,6 +172,24 @@ pub trait Summary {
     fn summarize(&self) -> String {
         format!("(Read more from {}...)", self.summarize_author())
     }
+
+    // Default implementations can call other trait methods, even ones without
+    // a default. Since this is written in terms of summarize, it picks up any
+    // overridden summarize automatically
+    fn word_count(&self) -> usize {
+        self.summarize().split_whitespace().count()
+    }
+
+    // The first n characters of the summary, with "..." appended if anything
+    // was cut off. Counting chars rather than bytes means we never slice
+    // through the middle of a multi-byte UTF-8 character
+    fn preview(&self, n: usize) -> String {
+        let summary = self.summarize();
+        match summary.char_indices().nth(n) {
+            Some((cut, _)) => format!("{}...", &summary[..cut]),
+            None => summary, // n chars or fewer, nothing to truncate
+        }
+    }
 }
 
 pub struct Article {
@@ -121,6 +219,18 @@ impl Summary for Tweet {
     }
 }
 
+// We can't write "impl<T: Summary> Display for T", since Display and most of
+// the types it could apply to are defined in other crates (the orphan rule).
+// The workaround is the newtype pattern: wrap the value in a local tuple struct
+// and implement the foreign trait on the wrapper instead
+pub struct Summarized<T: Summary>(pub T);
+
+impl<T: Summary> std::fmt::Display for Summarized<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.0.summarize())
+    }
+}
+
 // Calling code doesn't know the concrete type that will be returned; has to
 // rely on the interface, so to speak. However, using this "impl trait" syntax
 // for the return type has the restriction that only one concrete type can be
@@ -133,6 +243,115 @@ fn returns_summarizable() -> impl Summary {
     }
 }
 
+// We can also implement traits from the standard library on our own types,
+// which lets them plug into the rest of the language: Display gives us {}
+// formatting (and to_string() via the blanket impl mentioned above), FromStr
+// gives us str::parse, and the traits in std::ops overload operators like +.
+// A Fraction is always stored in lowest terms with a positive denominator, so
+// two equal fractions have identical fields and PartialEq can just be derived
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fraction {
+    num: i64,
+    den: i64,
+}
+
+// Euclid's algorithm; the result is always non-negative
+fn gcd(a: i64, b: i64) -> i64 {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+impl Fraction {
+    // Panics on a zero denominator, since that's a broken calling contract
+    // rather than something the caller can recover from. Use parse() to get a
+    // Result instead
+    pub fn new(num: i64, den: i64) -> Fraction {
+        if den == 0 {
+            panic!("Fraction denominator must be non-zero");
+        }
+        let sign = if den < 0 { -1 } else { 1 };
+        let divisor = gcd(num, den);
+        Fraction {
+            num: sign * num / divisor,
+            den: sign * den / divisor,
+        }
+    }
+}
+
+impl std::fmt::Display for Fraction {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.den == 1 {
+            write!(f, "{}", self.num)
+        } else {
+            write!(f, "{}/{}", self.num, self.den)
+        }
+    }
+}
+
+// Accepts "num/den" or a plain integer "num", i.e., anything Display outputs
+impl std::str::FromStr for Fraction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (num, den) = match s.trim().split_once('/') {
+            Some((num, den)) => (num.trim(), den.trim()),
+            None => (s.trim(), "1"),
+        };
+        let num: i64 = num
+            .parse()
+            .map_err(|_| format!("Invalid numerator in \"{}\"", s))?;
+        let den: i64 = den
+            .parse()
+            .map_err(|_| format!("Invalid denominator in \"{}\"", s))?;
+        if den == 0 {
+            return Err(format!("Zero denominator in \"{}\"", s));
+        }
+        Ok(Fraction::new(num, den))
+    }
+}
+
+impl std::ops::Add for Fraction {
+    type Output = Fraction;
+
+    fn add(self, other: Fraction) -> Fraction {
+        Fraction::new(
+            self.num * other.den + other.num * self.den,
+            self.den * other.den,
+        )
+    }
+}
+
+impl std::ops::Sub for Fraction {
+    type Output = Fraction;
+
+    fn sub(self, other: Fraction) -> Fraction {
+        Fraction::new(
+            self.num * other.den - other.num * self.den,
+            self.den * other.den,
+        )
+    }
+}
+
+impl std::ops::Mul for Fraction {
+    type Output = Fraction;
+
+    fn mul(self, other: Fraction) -> Fraction {
+        Fraction::new(self.num * other.num, self.den * other.den)
+    }
+}
+
+// Panics when dividing by zero, like integer division does
+impl std::ops::Div for Fraction {
+    type Output = Fraction;
+
+    fn div(self, other: Fraction) -> Fraction {
+        Fraction::new(self.num * other.den, self.den * other.num)
+    }
+}
+
 // Just like how Rust can often infer types, it can also infer the lifetimes of
 // references (i.e., the scope within which a given ref is valid). Sometimes we
 // have to explicitly tell Rust types when multiple are possible, and
@@ -179,6 +398,20 @@ fn longest<'a>(s1: &'a str, s2: &'a str) -> &'a str {
     }
 }
 
+// The same idea for any number of strings. Note that only the string slices
+// inside the array are annotated with 'a: the array itself can be borrowed for
+// a shorter (elided) lifetime, since the result refers to one of the strings,
+// not to the array holding them. Ties go to the first of the longest strings
+fn longest_of<'a>(strings: &[&'a str]) -> Option<&'a str> {
+    let mut longest = *strings.first()?;
+    for &s in strings {
+        if s.len() > longest.len() {
+            longest = s;
+        }
+    }
+    Some(longest)
+}
+
 // Lifetime param 'a here indicates that an instance of this struct can't
 // outlive the reference held in the part field. If there were more fields that
 // were references, we would need to provide a lifetime annotation for each of
@@ -212,11 +445,21 @@ impl<'a> ImportantExcerpt<'a> {
 
 fn main() {
     let num_list = vec![2, -3, 42, 0, 16];
-    let max = find_max(&num_list);
-    println!("Max of {:?} is {}", num_list, max);
+    match find_max(&num_list) {
+        Some(max) => println!("Max of {:?} is {}", num_list, max),
+        None => println!("{:?} is empty, so it has no max", num_list),
+    }
+    if let Some(min) = find_min(&num_list) {
+        println!("Min of {:?} is {}", num_list, min);
+    }
+    if let Some((min, max)) = find_min_max(&num_list) {
+        println!("Min and max of {:?} are {} and {}", num_list, min, max);
+    }
 
     let char_list = vec!['h', 'e', 'l', 'l', 'o'];
-    println!("Max of {:?} is {}", char_list, find_max(&char_list));
+    if let Some(max) = find_max(&char_list) {
+        println!("Max of {:?} is {}", char_list, max);
+    }
 
     let int_struct = Point { x: 2, y: -2 };
     let float_struct = Point { x: 2.12, y: -6.93 };
@@ -225,6 +468,16 @@ fn main() {
         "float_struct point is {} units away from origin",
         float_struct.dist_from_origin()
     );
+    println!(
+        "float_struct point is {} units away from (1, 1)",
+        float_struct.distance_to(&Point { x: 1.0, y: 1.0 })
+    );
+    println!(
+        "Point sum is {:?}",
+        Point { x: 1, y: 2 } + Point { x: 3, y: 4 }
+    );
+    let mixed = Point { x: 5, y: 10.4 }.mixup(Point { x: "Hello", y: 'c' });
+    println!("mixed point is ({}, {})", mixed.x, mixed.y);
 
     let tweet = returns_summarizable();
     println!("Tweet: {}", tweet.summarize());
@@ -235,6 +488,20 @@ fn main() {
         content: String::from("LOREM IPSUM!!"),
     };
     println!("Article: {}", article.summarize());
+    println!(
+        "Tweet summary has {} words, article summary has {}",
+        tweet.word_count(),
+        article.word_count()
+    );
+    println!("Article preview: {}", article.preview(8));
+    println!("Summarized tweet: {}", Summarized(tweet));
+
+    let half: Fraction = "2/4".parse().unwrap();
+    let third = Fraction::new(1, 3);
+    println!("{} + {} = {}", half, third, half + third);
+    println!("{} - {} = {}", half, third, half - third);
+    println!("{} * {} = {}", half, third, half * third);
+    println!("{} / {} = {}", half, third, half / third);
 
     let string1 = String::from("test");
     {
@@ -266,9 +533,207 @@ fn main() {
         res = longest(string1.as_str(), string2);
     }
     println!("2: Longest string is {}", res);
+    if let Some(res) = longest_of(&["a", "abc", string1.as_str(), "ab"]) {
+        println!("3: Longest string is {}", res);
+    }
 
     let ex = ImportantExcerpt {
         part: &string1,
     };
     ex.tst();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_max_of_empty_is_none() {
+        let empty: [i32; 0] = [];
+        assert_eq!(find_max(&empty), None);
+    }
+
+    #[test]
+    fn find_max_of_single_element() {
+        assert_eq!(find_max(&[7]), Some(7));
+    }
+
+    #[test]
+    fn find_max_with_negatives() {
+        assert_eq!(find_max(&[-8, -3, -42, -16]), Some(-3));
+        assert_eq!(find_max(&[2, -3, 42, 0, 16]), Some(42));
+    }
+
+    #[test]
+    fn mixup_takes_x_from_self_and_y_from_other() {
+        let p1: Point<i32, f64> = Point { x: 5, y: 10.4 };
+        let p2: Point<char, bool> = Point { x: 'c', y: true };
+        let p3: Point<i32, bool> = p1.mixup(p2);
+        assert_eq!(*p3.x(), 5);
+        assert!(p3.y);
+    }
+
+    #[test]
+    fn distance_between_points() {
+        let origin = Point { x: 0.0, y: 0.0 };
+        let p = Point { x: 3.0, y: 4.0 };
+        assert!((origin.distance_to(&p) - 5.0).abs() < f32::EPSILON);
+        assert!((p.distance_to(&origin) - 5.0).abs() < f32::EPSILON);
+        assert!((p.distance_to(&origin) - p.dist_from_origin()).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn add_points() {
+        assert_eq!(
+            Point { x: 1, y: 2 } + Point { x: 3, y: 4 },
+            Point { x: 4, y: 6 }
+        );
+        assert_eq!(
+            Point { x: 0.5, y: -1.0 } + Point { x: 0.25, y: 1.0 },
+            Point { x: 0.75, y: 0.0 }
+        );
+    }
+
+    #[test]
+    fn word_count_of_summaries() {
+        let article = Article {
+            author: String::from("hunter2"),
+            headline: String::from("Rust 2021 edition released"),
+            content: String::from("LOREM IPSUM!!"),
+        };
+        // "Rust 2021 edition released, by hunter2"
+        assert_eq!(article.word_count(), 6);
+
+        let tweet = Tweet {
+            username: String::from("hunter2"),
+            content: String::from("Hello, world!"),
+        };
+        // "(Read more from @hunter2...)"
+        assert_eq!(tweet.word_count(), 4);
+    }
+
+    #[test]
+    fn preview_of_short_summary_has_no_ellipsis() {
+        let tweet = Tweet {
+            username: String::from("hunter2"),
+            content: String::from("Hello, world!"),
+        };
+        assert_eq!(tweet.preview(100), "(Read more from @hunter2...)");
+        assert_eq!(tweet.preview(28), tweet.summarize()); // exactly n chars
+    }
+
+    #[test]
+    fn preview_of_long_summary_is_truncated() {
+        let article = Article {
+            author: String::from("hunter2"),
+            headline: String::from("Crème brûlée recipes"),
+            content: String::from("LOREM IPSUM!!"),
+        };
+        assert_eq!(article.preview(5), "Crème...");
+        assert_eq!(article.preview(11), "Crème brûlé...");
+        assert_eq!(article.preview(0), "...");
+    }
+
+    #[test]
+    fn longest_of_empty_is_none() {
+        assert_eq!(longest_of(&[]), None);
+    }
+
+    #[test]
+    fn longest_of_single() {
+        assert_eq!(longest_of(&["only"]), Some("only"));
+    }
+
+    #[test]
+    fn longest_of_picks_first_on_tie() {
+        assert_eq!(longest_of(&["ab", "cde", "fgh", "i"]), Some("cde"));
+        assert_eq!(longest_of(&["x", "yy", "zzz"]), Some("zzz"));
+    }
+
+    #[test]
+    fn summarized_displays_summary() {
+        let article = Article {
+            author: String::from("hunter2"),
+            headline: String::from("Hello, world!"),
+            content: String::from("LOREM IPSUM!!"),
+        };
+        let expected = article.summarize();
+        assert_eq!(format!("{}", Summarized(article)), expected);
+
+        let tweet = Tweet {
+            username: String::from("hunter2"),
+            content: String::from("Hello, world!"),
+        };
+        assert_eq!(
+            Summarized(tweet).to_string(),
+            "(Read more from @hunter2...)"
+        );
+    }
+
+    #[test]
+    fn find_min_cases() {
+        let empty: [i32; 0] = [];
+        assert_eq!(find_min(&empty), None);
+        assert_eq!(find_min(&[7]), Some(7));
+        assert_eq!(find_min(&[2, -3, 42, 0, 16]), Some(-3));
+    }
+
+    #[test]
+    fn find_min_max_of_empty_is_none() {
+        let empty: [i32; 0] = [];
+        assert_eq!(find_min_max(&empty), None);
+    }
+
+    #[test]
+    fn find_min_max_matches_separate_passes() {
+        let data = [5, -1, 9, 3, 9, -7, 0, 12, 4];
+        // every prefix length, so both odd and even lengths are covered, as
+        // well as the extremes landing in either slot of a pair
+        for len in 1..=data.len() {
+            let list = &data[..len];
+            assert_eq!(
+                find_min_max(list),
+                Some((find_min(list).unwrap(), find_max(list).unwrap())),
+                "list {:?}",
+                list
+            );
+        }
+        assert_eq!(find_min_max(&['h', 'e', 'l', 'l', 'o']), Some(('e', 'o')));
+        assert_eq!(find_min_max(&[3, 1]), Some((1, 3)));
+    }
+
+    #[test]
+    fn fraction_is_reduced() {
+        assert_eq!(Fraction::new(2, 4), Fraction::new(1, 2));
+        assert_eq!(Fraction::new(3, -6).to_string(), "-1/2");
+        assert_eq!(Fraction::new(4, 2).to_string(), "2");
+    }
+
+    #[test]
+    fn fraction_arithmetic() {
+        let half = Fraction::new(1, 2);
+        let third = Fraction::new(1, 3);
+        assert_eq!(half + third, Fraction::new(5, 6));
+        assert_eq!(half - third, Fraction::new(1, 6));
+        assert_eq!(half * third, Fraction::new(1, 6));
+        assert_eq!(half / third, Fraction::new(3, 2));
+        assert_eq!(half + half, Fraction::new(1, 1));
+    }
+
+    #[test]
+    fn fraction_parse_display_round_trip() {
+        for s in ["3/4", "-5/7", "2"] {
+            let f: Fraction = s.parse().unwrap();
+            assert_eq!(f.to_string(), s);
+        }
+        assert_eq!("6/8".parse::<Fraction>().unwrap().to_string(), "3/4");
+    }
+
+    #[test]
+    fn fraction_parse_errors() {
+        assert!("1/0".parse::<Fraction>().is_err());
+        assert!("one/2".parse::<Fraction>().is_err());
+        assert!("1/2/3".parse::<Fraction>().is_err());
+        assert!("".parse::<Fraction>().is_err());
+    }
+}
@@ -0,0 +1,151 @@
+use std::cmp::Ordering;
+
+// The game's state and rules, kept apart from the I/O in main.rs so they can
+// be tested without anyone typing guesses. range_start and range_end are the
+// bounds the secret is known to lie within; each wrong guess narrows them.
+// max_attempts is None for the classic game, where you can keep guessing
+// until you get it
+pub struct Game {
+    secret: u32,
+    range_start: u32,
+    range_end: u32,
+    attempts: u32,
+    max_attempts: Option<u32>,
+}
+
+impl Game {
+    pub fn new(secret: u32, range_start: u32, range_end: u32) -> Game {
+        Game {
+            secret,
+            range_start,
+            range_end,
+            attempts: 0,
+            max_attempts: None,
+        }
+    }
+
+    pub fn with_max_attempts(mut self, max_attempts: Option<u32>) -> Game {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    // Compares value with the secret. A guess that's too small or too big
+    // rules out everything on that side of it, so the bounds move just past
+    // the guess
+    pub fn guess(&mut self, value: u32) -> Ordering {
+        self.attempts += 1;
+        let result = value.cmp(&self.secret);
+        match result {
+            Ordering::Less => self.range_start = value + 1,
+            Ordering::Greater => self.range_end = value - 1,
+            Ordering::Equal => (),
+        }
+        result
+    }
+
+    pub fn range(&self) -> (u32, u32) {
+        (self.range_start, self.range_end)
+    }
+
+    pub fn secret(&self) -> u32 {
+        self.secret
+    }
+
+    // Only meaningful after a wrong guess: true once every allowed attempt
+    // has been used. Never true without a limit
+    pub fn out_of_attempts(&self) -> bool {
+        match self.max_attempts {
+            Some(max) => self.attempts >= max,
+            None => false,
+        }
+    }
+}
+
+// Reads the optional "start end" bounds from the command line (args as given
+// by env::args(), so the first one is the program name). With no bounds the
+// game is played over 1..=100
+pub fn parse_range<I: Iterator<Item = String>>(mut args: I) -> Result<(u32, u32), String> {
+    args.next(); // skip program name
+    let bounds: Vec<String> = args.collect();
+    match bounds.as_slice() {
+        [] => Ok((1, 100)),
+        [start, end] => {
+            let start: u32 = start
+                .parse()
+                .map_err(|_| format!("start must be a non-negative integer, got '{}'", start))?;
+            let end: u32 = end
+                .parse()
+                .map_err(|_| format!("end must be a non-negative integer, got '{}'", end))?;
+            if start >= end {
+                return Err(format!("start ({}) must be less than end ({})", start, end));
+            }
+            Ok((start, end))
+        }
+        _ => Err(String::from(
+            "expected either no arguments or both a start and an end",
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn guesses_narrow_the_range() {
+        let mut game = Game::new(42, 1, 100);
+        assert_eq!(game.guess(50), Ordering::Greater);
+        assert_eq!(game.range(), (1, 49));
+        assert_eq!(game.guess(25), Ordering::Less);
+        assert_eq!(game.range(), (26, 49));
+        assert_eq!(game.guess(40), Ordering::Less);
+        assert_eq!(game.range(), (41, 49));
+        assert_eq!(game.guess(42), Ordering::Equal);
+        assert_eq!(game.range(), (41, 49)); // a win leaves the range alone
+    }
+
+    #[test]
+    fn limited_game_runs_out_of_attempts() {
+        let mut game = Game::new(7, 1, 10).with_max_attempts(Some(2));
+        game.guess(1);
+        assert!(!game.out_of_attempts());
+        game.guess(2);
+        assert!(game.out_of_attempts());
+        assert_eq!(game.secret(), 7);
+    }
+
+    #[test]
+    fn unlimited_game_never_runs_out() {
+        let mut game = Game::new(7, 1, 1000);
+        for value in 8..1000 {
+            game.guess(value);
+        }
+        assert!(!game.out_of_attempts());
+    }
+
+    fn args(list: &[&str]) -> impl Iterator<Item = String> {
+        let mut all = vec![String::from("guessing_game")];
+        all.extend(list.iter().map(|s| s.to_string()));
+        all.into_iter()
+    }
+
+    #[test]
+    fn range_defaults_to_1_through_100() {
+        assert_eq!(parse_range(args(&[])), Ok((1, 100)));
+    }
+
+    #[test]
+    fn range_from_arguments() {
+        assert_eq!(parse_range(args(&["1", "1000"])), Ok((1, 1000)));
+    }
+
+    #[test]
+    fn invalid_ranges_are_rejected() {
+        assert!(parse_range(args(&["10", "10"])).is_err());
+        assert!(parse_range(args(&["20", "10"])).is_err());
+        assert!(parse_range(args(&["one", "10"])).is_err());
+        assert!(parse_range(args(&["-5", "10"])).is_err());
+        assert!(parse_range(args(&["10"])).is_err());
+        assert!(parse_range(args(&["1", "2", "3"])).is_err());
+    }
+}
@@ -1,15 +1,16 @@
 use rand::Rng;
 use std::cmp::Ordering;
+use std::env;
 use std::io;
+use std::process;
 
-fn main() {
-    let mut range_start = 1;
-    let mut range_end = 100;
-    let secret_number = rand::thread_rng().gen_range(range_start..=range_end);
-    // println!("The secret number is: {}", secret_number);
+use guessing_game::{parse_range, Game};
 
+// Keeps asking until the player types a number inside [start, end]. Anything
+// that isn't a number is skipped silently, as before; a number outside the
+// range gets a reminder, since it would otherwise widen the range again
+fn read_guess(start: u32, end: u32) -> u32 {
     loop {
-        println!("Guess the number in [{}, {}]!", range_start, range_end);
         println!("Please input your guess.");
         let mut guess = String::new();
         io::stdin()
@@ -24,22 +25,68 @@ fn main() {
                                 // values, regardless of what information they
                                 // contain)
         };
+        if guess < start || guess > end {
+            println!("Out of range, try [{}, {}]", start, end);
+            continue;
+        }
+        return guess;
+    }
+}
+
+fn main() {
+    // e.g. "guessing_game 1 1000"
+    let (range_start, range_end) = parse_range(env::args()).unwrap_or_else(|err| {
+        eprintln!("Invalid range: {}", err);
+        eprintln!("Usage: guessing_game [start end]");
+        process::exit(1);
+    });
+    let secret_number = rand::thread_rng().gen_range(range_start..=range_end);
+    // println!("The secret number is: {}", secret_number);
+    // MAX_ATTEMPTS=N turns on a lose condition; leave it unset to play for as
+    // long as it takes
+    let max_attempts = match env::var("MAX_ATTEMPTS") {
+        Ok(value) => match value.trim().parse() {
+            Ok(max) => Some(max),
+            Err(_) => {
+                eprintln!("Ignoring MAX_ATTEMPTS={}, it isn't a number", value);
+                None
+            }
+        },
+        Err(_) => None,
+    };
+    let mut game = Game::new(secret_number, range_start, range_end).with_max_attempts(max_attempts);
+
+    // How far the previous wrong guess was from the secret (None before the
+    // first guess), so each new guess can be compared against it
+    let mut last_distance: Option<u32> = None;
+
+    loop {
+        let (start, end) = game.range();
+        println!("Guess the number in [{}, {}]!", start, end);
+        let guess = read_guess(start, end);
 
         println!("You guessed: {}", guess);
 
-        match guess.cmp(&secret_number) {
-            Ordering::Less => {
-                println!("Too small!");
-                range_start = guess + 1;
-            }
-            Ordering::Greater => {
-                println!("Too big!");
-                range_end = guess - 1;
-            }
+        match game.guess(guess) {
+            Ordering::Less => println!("Too small!"),
+            Ordering::Greater => println!("Too big!"),
             Ordering::Equal => {
                 println!("You win!");
                 break;
             }
         }
+        let distance = guess.abs_diff(secret_number);
+        if let Some(last) = last_distance {
+            match distance.cmp(&last) {
+                Ordering::Less => println!("Warmer!"),
+                Ordering::Greater => println!("Colder!"),
+                Ordering::Equal => (), // just as close, on the other side
+            }
+        }
+        last_distance = Some(distance);
+        if game.out_of_attempts() {
+            println!("You lose! The number was {}", game.secret());
+            break;
+        }
     }
 }
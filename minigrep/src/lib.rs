@@ -1,6 +1,20 @@
 use std::env;
 use std::error::Error;
+use std::fmt;
 use std::fs;
+use std::io;
+use std::ops::Range;
+
+use regex::{Regex, RegexBuilder};
+
+// How the bytes of the file should be turned into text
+#[derive(Debug, PartialEq)]
+pub enum Encoding {
+    Utf8,
+    // ISO-8859-1: every byte is a character, with the same value as its
+    // Unicode code point, so decoding can never fail
+    Latin1,
+}
 
 // Configuration variables should be grouped into a single structure so that
 // their purpose becomes more clear.
@@ -8,16 +22,89 @@ pub struct Config {
     pub query: String,
     pub fname: String,
     pub case_sensitive: bool,
+    // stop searching after this many matching lines (-m N)
+    pub max_count: Option<usize>,
+    // print only the matched parts of each line, one per output line (-o)
+    pub only_matching: bool,
+    // print results as a JSON array instead of plain text (--json)
+    pub json: bool,
+    // how to decode the file (--encoding utf8|latin1, default utf8)
+    pub encoding: Encoding,
+    // replace invalid UTF-8 with U+FFFD instead of failing (--lossy)
+    pub lossy: bool,
+    // skip searching and just report how many lines the file has
+    // (--line-count-only)
+    pub line_count_only: bool,
+    // goes between the fields of a prefixed output line, like the file name
+    // and count printed by --line-count-only (--field-separator SEP, default
+    // ":"). "\t" and "\0" are accepted for a tab or a NUL, since a shell
+    // can't pass a NUL byte in an argument
+    pub field_sep: String,
+    // prefix each result with the column its match starts at (--column)
+    pub column: bool,
+    // treat the query as a regular expression instead of plain text (--regex)
+    pub regex: bool,
 }
 
 impl Config {
-    pub fn new<'a, I: Iterator<Item = String>>(mut args: I) -> Result<Config, &'static str> {
+    pub fn new<I: Iterator<Item = String>>(mut args: I) -> Result<Config, &'static str> {
         args.next(); // skip program name
-        let query = match args.next() {
+        let mut query = None;
+        let mut fname = None;
+        let mut max_count = None;
+        let mut only_matching = false;
+        let mut json = false;
+        let mut encoding = Encoding::Utf8;
+        let mut lossy = false;
+        let mut line_count_only = false;
+        let mut field_sep = String::from(":");
+        let mut column = false;
+        let mut regex = false;
+        // flags may appear anywhere; everything else is positional
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-m" => {
+                    let n = match args.next() {
+                        Some(n) => n,
+                        None => return Err("Didn't get a value for -m"),
+                    };
+                    max_count = match n.parse() {
+                        Ok(n) => Some(n),
+                        Err(_) => return Err("-m expects a non-negative integer"),
+                    };
+                }
+                "-o" => only_matching = true,
+                "--json" => json = true,
+                "--lossy" => lossy = true,
+                "--line-count-only" => line_count_only = true,
+                "--column" => column = true,
+                "--regex" => regex = true,
+                "--field-separator" => {
+                    field_sep = match args.next().as_deref() {
+                        Some("\\t") => String::from("\t"),
+                        Some("\\0") => String::from("\0"),
+                        Some(sep) => String::from(sep),
+                        None => return Err("Didn't get a value for --field-separator"),
+                    };
+                }
+                "--encoding" => {
+                    encoding = match args.next().as_deref() {
+                        Some("utf8") | Some("utf-8") => Encoding::Utf8,
+                        Some("latin1") | Some("latin-1") | Some("iso-8859-1") => Encoding::Latin1,
+                        Some(_) => return Err("Unsupported encoding, expected utf8 or latin1"),
+                        None => return Err("Didn't get a value for --encoding"),
+                    };
+                }
+                _ if query.is_none() => query = Some(arg),
+                _ if fname.is_none() => fname = Some(arg),
+                _ => (), // extra positional arguments are ignored
+            }
+        }
+        let query = match query {
             Some(arg) => arg,
             None => return Err("Didn't get a query"),
         };
-        let fname = match args.next() {
+        let fname = match fname {
             Some(arg) => arg,
             None => return Err("Didn't get a filename"),
         };
@@ -27,39 +114,339 @@ impl Config {
             query,
             fname,
             case_sensitive,
+            max_count,
+            only_matching,
+            json,
+            encoding,
+            lossy,
+            line_count_only,
+            field_sep,
+            column,
+            regex,
         })
     }
 }
 
+impl Config {
+    // The built-in Matcher the flags ask for. Compiling a regex can fail, so
+    // this is where a bad --regex pattern gets reported
+    pub fn matcher(&self) -> Result<Box<dyn Matcher>, AppError> {
+        if self.regex {
+            let matcher =
+                RegexMatcher::new(&self.query, self.case_sensitive).map_err(AppError::Pattern)?;
+            Ok(Box::new(matcher))
+        } else {
+            Ok(Box::new(SubstringMatcher::new(
+                &self.query,
+                self.case_sensitive,
+            )))
+        }
+    }
+}
+
+// Lets a plain Vec of arguments (e.g. one built in a test) be turned into a
+// Config with Config::try_from(args) or args.try_into(). Like env::args(), the
+// first element is taken to be the program name
+impl TryFrom<Vec<String>> for Config {
+    type Error = &'static str;
+
+    fn try_from(args: Vec<String>) -> Result<Config, Self::Error> {
+        Config::new(args.into_iter())
+    }
+}
+
+// fs::read_to_string errors out on the first invalid UTF-8 sequence, so for
+// other encodings (or a lossy read) we read the raw bytes and decode them
+// ourselves
+pub fn read_contents(config: &Config) -> io::Result<String> {
+    match config.encoding {
+        // from_utf8_lossy only allocates a new String if it had to replace
+        // something; into_owned gives us an owned String either way
+        Encoding::Utf8 if config.lossy => {
+            Ok(String::from_utf8_lossy(&fs::read(&config.fname)?).into_owned())
+        }
+        Encoding::Utf8 => fs::read_to_string(&config.fname),
+        Encoding::Latin1 => Ok(fs::read(&config.fname)?
+            .into_iter()
+            .map(char::from) // byte value == code point
+            .collect()),
+    }
+}
+
 pub fn run(config: Config) -> Result<(), Box<dyn Error>> {
-    let contents = fs::read_to_string(config.fname)?;
-    let results = if config.case_sensitive {
-        search(&config.query, &contents)
-    } else {
-        search_case_insensitive(&config.query, &contents)
-    };
-    for line in results {
-        println!("{}", line);
+    if config.line_count_only {
+        let contents = read_contents(&config)?;
+        let count = count_lines(&contents).to_string();
+        println!("{}", join_fields(&config, &[&config.fname, &count]));
+        return Ok(());
+    }
+    let report = search_all(&config)?;
+    if config.json {
+        println!("{}", report.to_json());
+        return Ok(());
+    }
+    // each Match is already a whole line, or a single occurrence with -o
+    for file in &report.files {
+        for m in &file.matches {
+            if config.column {
+                println!(
+                    "{}",
+                    join_fields(&config, &[&m.column.to_string(), &m.text])
+                );
+            } else {
+                println!("{}", m.text);
+            }
+        }
     }
     // Returning () is the idiomatic way to indicate that we are calling a
     // function for its side effects only (doesn't return a value we need)
     Ok(())
 }
 
+// Every output line that's made of several fields joins them the same way
+fn join_fields(config: &Config, fields: &[&str]) -> String {
+    fields.join(&config.field_sep)
+}
+
+// A structured version of what run prints, for using minigrep as a library.
+// Each Match is a matching line, or with -o, one occurrence within a line.
+// byte_offset counts from the start of the file (like grep -b), and text is
+// owned so the report doesn't borrow from the file contents
+#[derive(Debug, PartialEq)]
+pub struct Match {
+    pub line_number: usize, // 1-based
+    pub byte_offset: usize,
+    // 1-based, counted in chars rather than bytes, of where the match starts
+    // within its line (the first match, for a whole line)
+    pub column: usize,
+    pub text: String,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct FileResult {
+    pub path: String,
+    pub matches: Vec<Match>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct SearchReport {
+    pub files: Vec<FileResult>,
+}
+
+impl SearchReport {
+    // One {"file", "line_number", "text"} object per match. serde_json takes
+    // care of escaping quotes, backslashes and control characters in the text
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut records = Vec::new();
+        for file in &self.files {
+            for m in &file.matches {
+                records.push(serde_json::json!({
+                    "file": file.path,
+                    "line_number": m.line_number,
+                    "text": m.text,
+                }));
+            }
+        }
+        serde_json::Value::Array(records)
+    }
+}
+
+// Errors a library caller might want to tell apart, rather than a bare
+// Box<dyn Error>. Io keeps the path, since io::Error doesn't say which file
+// it was about
+#[derive(Debug)]
+pub enum AppError {
+    Io { path: String, source: io::Error },
+    Pattern(regex::Error), // the --regex query didn't compile
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AppError::Io { path, source } => write!(f, "{}: {}", path, source),
+            AppError::Pattern(e) => write!(f, "invalid pattern: {}", e),
+        }
+    }
+}
+
+impl Error for AppError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            AppError::Io { source, .. } => Some(source),
+            AppError::Pattern(e) => Some(e),
+        }
+    }
+}
+
+pub fn search_all(config: &Config) -> Result<SearchReport, AppError> {
+    let matcher = config.matcher()?;
+    let contents = read_contents(config).map_err(|source| AppError::Io {
+        path: config.fname.clone(),
+        source,
+    })?;
+    Ok(report(config, matcher.as_ref(), &contents))
+}
+
+fn report(config: &Config, matcher: &dyn Matcher, contents: &str) -> SearchReport {
+    let mut matches = Vec::new();
+    for (line_number, line_offset, line) in search_located(matcher, contents, config.max_count) {
+        let ranges = matcher.find_all(line);
+        if config.only_matching {
+            for range in ranges {
+                matches.push(Match {
+                    line_number,
+                    byte_offset: line_offset + range.start,
+                    column: char_column(line, range.start),
+                    text: line[range].to_string(),
+                });
+            }
+        } else {
+            matches.push(Match {
+                line_number,
+                byte_offset: line_offset,
+                column: ranges
+                    .first()
+                    .map_or(1, |range| char_column(line, range.start)),
+                text: line.to_string(),
+            });
+        }
+    }
+    SearchReport {
+        files: vec![FileResult {
+            path: config.fname.clone(),
+            matches,
+        }],
+    }
+}
+
+// Turns a byte index within line into a 1-based column. Multi-byte characters
+// like 'é' take up several bytes but only one column, so we count the chars
+// before the index rather than using the index itself. (A char isn't quite
+// what a reader sees as one character, since accents can be separate combining
+// chars, but it's a close enough approximation without a grapheme library)
+pub fn char_column(line: &str, byte_index: usize) -> usize {
+    line[..byte_index].chars().count() + 1
+}
+
+// Deciding whether a line matches is behind a trait, so the search doesn't care
+// how it's done: search_with takes any &dyn Matcher, whether it's one of the
+// built-in ones below or something a library user wrote (a fuzzy matcher, say).
+// find_all gives the byte ranges of the individual matches within a line, for
+// -o and --column; matchers that don't have a notion of individual matches can
+// rely on the default, which treats a matching line as one big match
+pub trait Matcher {
+    fn matches(&self, line: &str) -> bool;
+
+    fn find_all(&self, line: &str) -> Vec<Range<usize>> {
+        if self.matches(line) {
+            let whole_line = 0..line.len();
+            vec![whole_line]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+// Plain text matching, what minigrep has always done. A case insensitive
+// matcher lowercases the query once up front rather than on every line
+pub struct SubstringMatcher {
+    query: String,
+    case_sensitive: bool,
+}
+
+impl SubstringMatcher {
+    pub fn new(query: &str, case_sensitive: bool) -> SubstringMatcher {
+        let query = if case_sensitive {
+            query.to_string()
+        } else {
+            query.to_lowercase()
+        };
+        SubstringMatcher {
+            query,
+            case_sensitive,
+        }
+    }
+}
+
+impl Matcher for SubstringMatcher {
+    fn matches(&self, line: &str) -> bool {
+        if self.case_sensitive {
+            line.contains(&self.query)
+        } else {
+            line.to_lowercase().contains(&self.query)
+        }
+    }
+
+    fn find_all(&self, line: &str) -> Vec<Range<usize>> {
+        match_ranges(&self.query, line, self.case_sensitive)
+    }
+}
+
+// Regular expression matching (--regex), using the regex crate
+pub struct RegexMatcher {
+    regex: Regex,
+}
+
+impl RegexMatcher {
+    pub fn new(pattern: &str, case_sensitive: bool) -> Result<RegexMatcher, regex::Error> {
+        let regex = RegexBuilder::new(pattern)
+            .case_insensitive(!case_sensitive)
+            .build()?;
+        Ok(RegexMatcher { regex })
+    }
+}
+
+impl Matcher for RegexMatcher {
+    fn matches(&self, line: &str) -> bool {
+        self.regex.is_match(line)
+    }
+
+    fn find_all(&self, line: &str) -> Vec<Range<usize>> {
+        self.regex
+            .find_iter(line)
+            .map(|m| m.range())
+            .filter(|range| !range.is_empty()) // same as an empty query
+            .collect()
+    }
+}
+
+// Every matching line, using whichever Matcher the caller hands us
+pub fn search_with<'a>(
+    matcher: &dyn Matcher,
+    contents: &'a str,
+    max_count: Option<usize>,
+) -> Vec<&'a str> {
+    contents
+        .lines()
+        .filter(|line| matcher.matches(line))
+        .take(max_count.unwrap_or(usize::MAX))
+        .collect()
+}
+
 // iterator adapter and consumer approach. Iterators are a zero-overhead
-// abstraction and may communicate intent more clearly
-pub fn search<'a>(query: &str, contents: &'a str) -> Vec<&'a str> {
+// abstraction and may communicate intent more clearly. Iterators are also
+// lazy, so take(n) stops pulling lines from the file once n matches are found
+pub fn search<'a>(query: &str, contents: &'a str, max_count: Option<usize>) -> Vec<&'a str> {
     contents
         .lines()
         .filter(|line| line.contains(query))
+        .take(max_count.unwrap_or(usize::MAX))
         .collect()
 }
 
 // original code with mutable state
-pub fn search_case_insensitive<'a>(query: &str, contents: &'a str) -> Vec<&'a str> {
+pub fn search_case_insensitive<'a>(
+    query: &str,
+    contents: &'a str,
+    max_count: Option<usize>,
+) -> Vec<&'a str> {
     let query = query.to_lowercase(); // creates new data (no longer a reference)
+    let max_count = max_count.unwrap_or(usize::MAX);
     let mut res = Vec::new();
     for line in contents.lines() {
+        if res.len() >= max_count {
+            break; // same early stop as take(n) in the iterator version
+        }
         if line.to_lowercase().contains(&query) {
             res.push(line);
         }
@@ -67,6 +454,118 @@ pub fn search_case_insensitive<'a>(query: &str, contents: &'a str) -> Vec<&'a st
     res
 }
 
+// Counts lines the same way lines() splits them: a trailing newline ends the
+// last line rather than starting a new, empty one (so "a\nb\n" and "a\nb"
+// both have 2 lines, matching wc -l for files that end in a newline). An
+// empty file has 0 lines
+pub fn count_lines(contents: &str) -> usize {
+    contents.lines().count()
+}
+
+// (line number, byte offset of the line, line) for each matching line
+fn search_located<'a>(
+    matcher: &dyn Matcher,
+    contents: &'a str,
+    max_count: Option<usize>,
+) -> Vec<(usize, usize, &'a str)> {
+    lines_with_offsets(contents)
+        .enumerate()
+        .filter(|(_, (_, line))| matcher.matches(line))
+        .take(max_count.unwrap_or(usize::MAX))
+        .map(|(i, (offset, line))| (i + 1, offset, line))
+        .collect()
+}
+
+// Splits contents the same way lines() does (dropping the "\n" or "\r\n" at
+// the end of each line), but also yields the byte offset each line starts at
+fn lines_with_offsets(contents: &str) -> impl Iterator<Item = (usize, &str)> {
+    let mut offset = 0;
+    contents.split_inclusive('\n').map(move |chunk| {
+        let start = offset;
+        offset += chunk.len();
+        let line = match chunk.strip_suffix('\n') {
+            Some(line) => line.strip_suffix('\r').unwrap_or(line),
+            None => chunk,
+        };
+        (start, line)
+    })
+}
+
+// Yields the byte index of each non-overlapping occurrence of query in line,
+// scanning left to right (so "aa" occurs in "aaaa" at 0 and 2, not 0, 1, 2).
+// The iterator is lazy, and only borrows line: query is copied so that the
+// caller's query doesn't have to outlive the iterator. An empty query yields
+// nothing
+pub fn match_positions<'a>(query: &str, line: &'a str) -> impl Iterator<Item = usize> + 'a {
+    let query = query.to_string();
+    let mut start = 0;
+    std::iter::from_fn(move || {
+        if query.is_empty() {
+            return None;
+        }
+        let pos = start + line[start..].find(query.as_str())?;
+        start = pos + query.len();
+        Some(pos)
+    })
+}
+
+// Returns each non-overlapping occurrence of query within line. The returned
+// slices borrow from line, so a case insensitive match still gives back the
+// text as it was written in the file rather than the lowercased query
+pub fn matches_in_line<'a>(query: &str, line: &'a str, case_sensitive: bool) -> Vec<&'a str> {
+    match_ranges(query, line, case_sensitive)
+        .into_iter()
+        .map(|range| &line[range])
+        .collect()
+}
+
+// The byte range of each occurrence that matches_in_line returns
+fn match_ranges(query: &str, line: &str, case_sensitive: bool) -> Vec<Range<usize>> {
+    if query.is_empty() {
+        return Vec::new(); // an empty match isn't worth printing
+    }
+    if case_sensitive {
+        return match_positions(query, line)
+            .map(|i| i..i + query.len())
+            .collect();
+    }
+    // Lowercasing can change the byte length of some characters, so we can't
+    // search a lowercased copy and reuse its indices. Instead, try to match
+    // starting at each char boundary of the original line
+    let query: Vec<char> = query.to_lowercase().chars().collect();
+    let mut res = Vec::new();
+    let mut start = 0;
+    while let Some(c) = line[start..].chars().next() {
+        match match_len_ignore_case(&line[start..], &query) {
+            Some(len) => {
+                res.push(start..start + len);
+                start += len;
+            }
+            None => start += c.len_utf8(),
+        }
+    }
+    res
+}
+
+// Number of bytes at the start of haystack that match the (already lowercased)
+// query, if the whole query matches and ends on a char boundary
+fn match_len_ignore_case(haystack: &str, query: &[char]) -> Option<usize> {
+    let mut expected = query.iter();
+    let mut remaining = query.len();
+    for (i, c) in haystack.char_indices() {
+        for lc in c.to_lowercase() {
+            match expected.next() {
+                Some(&qc) if qc == lc => remaining -= 1,
+                _ => return None,
+            }
+        }
+        if remaining == 0 {
+            return Some(i + c.len_utf8());
+        }
+    }
+    None
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -103,7 +602,7 @@ And we all went to heaven in a little row boat
 There was nothing to fear and nothing to doubt";
         assert_eq!(
             vec!["There was nothing to fear and nothing to doubt"],
-            search(query, contents)
+            search(query, contents, None)
         );
     }
 
@@ -119,7 +618,414 @@ And all the things I used to see";
                 "A moon full of stars and astral cars",
                 "And all the things I used to see"
             ],
-            search_case_insensitive(query, contents)
+            search_case_insensitive(query, contents, None)
+        );
+    }
+
+    #[test]
+    fn max_count_stops_after_first_match() {
+        let args = [
+            String::from("bin_name"),
+            String::from("-m"),
+            String::from("1"),
+            String::from("the"),
+            String::from("poem.txt"),
+        ];
+        let config = Config::new(args.into_iter()).unwrap();
+        assert_eq!(config.max_count, Some(1));
+
+        let contents = "\
+All the things I used to see
+And all the things I used to see";
+        assert_eq!(
+            vec!["All the things I used to see"],
+            search(&config.query, contents, config.max_count)
+        );
+        assert_eq!(
+            vec!["All the things I used to see"],
+            search_case_insensitive(&config.query, contents, config.max_count)
+        );
+    }
+
+    #[test]
+    fn only_matching_flag_is_parsed() {
+        let args = [
+            String::from("bin_name"),
+            String::from("-o"),
+            String::from("arg1"),
+            String::from("arg2"),
+        ];
+        let config = Config::new(args.into_iter()).unwrap();
+        assert!(config.only_matching);
+        assert_eq!(config.query, "arg1");
+        assert_eq!(config.fname, "arg2");
+    }
+
+    #[test]
+    fn config_try_from_vec() {
+        let args: Vec<String> = ["bin_name", "--json", "-m", "3", "needle", "haystack.txt"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let config = Config::try_from(args).unwrap();
+        assert_eq!(config.query, "needle");
+        assert_eq!(config.fname, "haystack.txt");
+        assert_eq!(config.max_count, Some(3));
+        assert!(config.json);
+        assert!(!config.only_matching);
+        assert_eq!(config.encoding, Encoding::Utf8);
+
+        let missing_file: Result<Config, _> =
+            vec![String::from("bin_name"), String::from("needle")].try_into();
+        assert_eq!(missing_file.err(), Some("Didn't get a filename"));
+    }
+
+    #[test]
+    fn matches_in_line_finds_each_occurrence() {
+        let line = "There was nothing to fear and nothing to doubt";
+        assert_eq!(
+            vec!["nothing", "nothing"],
+            matches_in_line("nothing", line, true)
+        );
+        assert!(matches_in_line("river", line, true).is_empty());
+    }
+
+    #[test]
+    fn matches_in_line_keeps_original_case() {
+        let line = "And all the things I used to see, and AND";
+        assert_eq!(
+            vec!["And", "and", "AND"],
+            matches_in_line("and", line, false)
+        );
+        assert_eq!(vec!["and"], matches_in_line("and", line, true));
+    }
+
+    fn to_json(config: &Config, contents: &str) -> serde_json::Value {
+        report(config, config.matcher().unwrap().as_ref(), contents).to_json()
+    }
+
+    // A case-sensitive search of poem.txt with every option at its default
+    fn test_config(query: &str) -> Config {
+        Config {
+            query: String::from(query),
+            fname: String::from("poem.txt"),
+            case_sensitive: true,
+            max_count: None,
+            only_matching: false,
+            json: false,
+            encoding: Encoding::Utf8,
+            lossy: false,
+            line_count_only: false,
+            field_sep: String::from(":"),
+            column: false,
+            regex: false,
+        }
+    }
+
+    fn json_config(query: &str, only_matching: bool) -> Config {
+        Config {
+            only_matching,
+            json: true,
+            ..test_config(query)
+        }
+    }
+
+    #[test]
+    fn json_output_has_file_line_and_text() {
+        let contents = "\
+I jumped into the river
+Black-eyed angels swam with me
+I jumped in the river and what did I see?";
+        assert_eq!(
+            to_json(&json_config("river", false), contents),
+            serde_json::json!([
+                {"file": "poem.txt", "line_number": 1, "text": "I jumped into the river"},
+                {
+                    "file": "poem.txt",
+                    "line_number": 3,
+                    "text": "I jumped in the river and what did I see?"
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn json_output_escapes_special_characters() {
+        let contents = "say \"hi\"\tand \\ bye";
+        let out = to_json(&json_config("hi", false), contents).to_string();
+        assert!(out.contains(r#""text":"say \"hi\"\tand \\ bye""#));
+        let parsed: serde_json::Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(parsed[0]["text"], contents);
+    }
+
+    #[test]
+    fn json_output_is_valid_with_no_matches() {
+        let out = to_json(&json_config("missing", false), "nothing here").to_string();
+        assert_eq!(out, "[]");
+    }
+
+    #[test]
+    fn json_output_with_only_matching() {
+        let out = to_json(
+            &json_config("to", true),
+            "nothing to fear, nothing to doubt",
+        );
+        assert_eq!(out.as_array().unwrap().len(), 2);
+        assert_eq!(out[1]["text"], "to");
+        assert_eq!(out[1]["line_number"], 1);
+    }
+
+    #[test]
+    fn match_positions_multiple_occurrences() {
+        let line = "There was nothing to fear and nothing to doubt";
+        assert_eq!(
+            match_positions("nothing", line).collect::<Vec<_>>(),
+            vec![10, 30]
+        );
+        assert_eq!(
+            match_positions("to", line).collect::<Vec<_>>(),
+            vec![18, 38]
+        );
+    }
+
+    #[test]
+    fn match_positions_overlapping_pattern() {
+        assert_eq!(
+            match_positions("aa", "aaaa").collect::<Vec<_>>(),
+            vec![0, 2]
+        );
+        assert_eq!(match_positions("aba", "ababa").collect::<Vec<_>>(), vec![0]);
+    }
+
+    #[test]
+    fn match_positions_no_occurrences() {
+        assert_eq!(match_positions("river", "A moon full of stars").count(), 0);
+        assert_eq!(match_positions("", "A moon full of stars").count(), 0);
+        assert_eq!(match_positions("moon", "").count(), 0);
+    }
+
+    #[test]
+    fn match_positions_outlives_query() {
+        let line = "é and é";
+        let positions: Vec<usize> = {
+            let query = String::from("é");
+            match_positions(&query, line).collect()
+        };
+        assert_eq!(positions, vec![0, 7]); // byte indices, é is 2 bytes
+    }
+
+    // Writes bytes to a file in the system's temp directory, for tests that
+    // need to read from disk. The process id keeps concurrent test runs from
+    // sharing a file; each test removes its file once it's done reading
+    fn temp_file(name: &str, bytes: &[u8]) -> String {
+        let file_name = format!("minigrep_test_{}_{}", std::process::id(), name);
+        let path = env::temp_dir().join(file_name);
+        fs::write(&path, bytes).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn search_all_reports_line_numbers_and_offsets() {
+        let path = temp_file(
+            "report.txt",
+            b"first line\r\nthe cat sat\nno match\nThe end the end\n",
+        );
+        let mut config = test_config("the");
+        config.fname = path.clone();
+        let lines = search_all(&config).unwrap();
+        // with -o, each occurrence gets its own offset
+        config.only_matching = true;
+        let occurrences = search_all(&config).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            lines,
+            SearchReport {
+                files: vec![FileResult {
+                    path: path.clone(),
+                    matches: vec![
+                        Match {
+                            line_number: 2,
+                            byte_offset: 12,
+                            column: 1,
+                            text: String::from("the cat sat"),
+                        },
+                        Match {
+                            line_number: 4,
+                            byte_offset: 33,
+                            column: 9,
+                            text: String::from("The end the end"),
+                        },
+                    ],
+                }],
+            }
+        );
+
+        let found: Vec<(usize, usize, &str)> = occurrences.files[0]
+            .matches
+            .iter()
+            .map(|m| (m.line_number, m.byte_offset, m.text.as_str()))
+            .collect();
+        assert_eq!(found, vec![(2, 12, "the"), (4, 41, "the")]);
+    }
+
+    #[test]
+    fn search_all_missing_file_is_an_io_error() {
+        let mut config = test_config("the");
+        config.fname = String::from("definitely/not/a/real/file.txt");
+        match search_all(&config) {
+            Err(AppError::Io { path, .. }) => assert_eq!(path, "definitely/not/a/real/file.txt"),
+            other => panic!("expected an Io error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn encoding_flag_is_parsed() {
+        let args = ["bin_name", "--encoding", "latin1", "query", "fname"];
+        let config = Config::new(args.iter().map(|s| s.to_string())).unwrap();
+        assert_eq!(config.encoding, Encoding::Latin1);
+
+        let args = ["bin_name", "query", "fname", "--encoding", "ebcdic"];
+        assert!(Config::new(args.iter().map(|s| s.to_string())).is_err());
+    }
+
+    #[test]
+    fn latin1_file_is_searchable() {
+        // "caf\xe9 au lait" in latin-1; 0xE9 on its own isn't valid UTF-8
+        let fname = temp_file("latin1.txt", b"the menu\ncaf\xe9 au lait\n");
+        let mut config = test_config("é");
+        config.fname = fname.clone();
+        let as_utf8 = read_contents(&config);
+        config.encoding = Encoding::Latin1;
+        let as_latin1 = read_contents(&config);
+        fs::remove_file(&fname).unwrap();
+
+        assert!(as_utf8.is_err());
+        let contents = as_latin1.unwrap();
+        assert_eq!(vec!["café au lait"], search("é", &contents, None));
+    }
+
+    #[test]
+    fn lossy_read_replaces_invalid_utf8() {
+        // 0xFF can never appear in UTF-8
+        let fname = temp_file("lossy.txt", b"good line\nbad \xff line\n");
+        let mut config = test_config("line");
+        config.fname = fname.clone();
+        let strict = read_contents(&config);
+        config.lossy = true;
+        let lossy = read_contents(&config);
+        fs::remove_file(&fname).unwrap();
+
+        assert!(strict.is_err());
+        let contents = lossy.unwrap();
+        assert_eq!(
+            vec!["good line", "bad \u{FFFD} line"],
+            search(&config.query, &contents, None)
+        );
+    }
+
+    #[test]
+    fn count_lines_with_and_without_trailing_newline() {
+        assert_eq!(count_lines("one\ntwo\nthree"), 3);
+        assert_eq!(count_lines("one\ntwo\nthree\n"), 3);
+        assert_eq!(count_lines("one\n\nthree\n"), 3); // blank lines count
+        assert_eq!(count_lines("\n"), 1);
+    }
+
+    #[test]
+    fn count_lines_of_empty_file() {
+        assert_eq!(count_lines(""), 0);
+    }
+
+    #[test]
+    fn line_count_only_flag_is_parsed() {
+        let args = ["bin_name", "query", "poem.txt", "--line-count-only"];
+        let config = Config::new(args.iter().map(|s| s.to_string())).unwrap();
+        assert!(config.line_count_only);
+        assert_eq!(count_lines(&read_contents(&config).unwrap()), 20);
+    }
+
+    #[test]
+    fn field_separator_is_used_between_fields() {
+        let args = ["bin_name", "--field-separator", "\\t", "query", "poem.txt"];
+        let config = Config::new(args.iter().map(|s| s.to_string())).unwrap();
+        assert_eq!(config.field_sep, "\t");
+        assert_eq!(join_fields(&config, &["poem.txt", "20"]), "poem.txt\t20");
+
+        let default = test_config("query");
+        assert_eq!(join_fields(&default, &["poem.txt", "20"]), "poem.txt:20");
+    }
+
+    #[test]
+    fn column_counts_chars_not_bytes() {
+        let args = ["bin_name", "--column", "needle", "poem.txt"];
+        let config = Config::new(args.iter().map(|s| s.to_string())).unwrap();
+        assert!(config.column);
+
+        // 'é' and '✓' are 2 and 3 bytes long, so the match starts at byte 8
+        // even though only 5 characters come before it
+        let contents = "é✓ a needle";
+        let matcher = config.matcher().unwrap();
+        let m = &report(&config, matcher.as_ref(), contents).files[0].matches[0];
+        assert_eq!(contents.find("needle"), Some(8));
+        assert_eq!(m.column, 6);
+        assert_eq!(char_column(contents, 8), 6);
+    }
+
+    // Matches any line with more than the given number of characters
+    struct LongerThan(usize);
+
+    impl Matcher for LongerThan {
+        fn matches(&self, line: &str) -> bool {
+            line.chars().count() > self.0
+        }
+    }
+
+    #[test]
+    fn search_with_custom_matcher() {
+        let contents = "\
+short
+a much longer line than the rest
+tiny
+another line that is long enough";
+        assert_eq!(
+            vec![
+                "a much longer line than the rest",
+                "another line that is long enough"
+            ],
+            search_with(&LongerThan(10), contents, None)
         );
+        assert_eq!(
+            vec!["a much longer line than the rest"],
+            search_with(&LongerThan(10), contents, Some(1))
+        );
+        // the default find_all treats the whole line as the match
+        let whole_line = 0..5;
+        assert_eq!(LongerThan(3).find_all("hello"), vec![whole_line]);
+        assert!(LongerThan(3).find_all("hi").is_empty());
+    }
+
+    #[test]
+    fn regex_flag_selects_regex_matcher() {
+        let args = ["bin_name", "--regex", "-o", "r[a-z]*s", "poem.txt"];
+        let config = Config::new(args.iter().map(|s| s.to_string())).unwrap();
+        assert!(config.regex);
+        let matcher = config.matcher().unwrap();
+        assert!(matcher.matches("all my lovers"));
+        assert!(!matcher.matches("the river"));
+        let report = report(&config, matcher.as_ref(), "rivers and roads\nnone here");
+        let texts: Vec<&str> = report.files[0]
+            .matches
+            .iter()
+            .map(|m| m.text.as_str())
+            .collect();
+        assert_eq!(texts, vec!["rivers", "roads"]);
+    }
+
+    #[test]
+    fn bad_regex_is_a_pattern_error() {
+        let args = ["bin_name", "--regex", "(unclosed", "poem.txt"];
+        let config = Config::new(args.iter().map(|s| s.to_string())).unwrap();
+        assert!(matches!(config.matcher(), Err(AppError::Pattern(_))));
     }
 }
@@ -11,17 +11,102 @@ pub struct Breakfast {
 // instance of Breakfast because we wouldn't be able to initialize the
 // private field
 impl Breakfast {
-    pub fn summer(toast: &str) -> Breakfast {
+    fn with_fruit(toast: &str, fruit: &str) -> Breakfast {
         Breakfast {
             toast: String::from(toast),
-            seasonal_fruit: String::from("peaches"),
+            seasonal_fruit: String::from(fruit),
         }
     }
+
+    pub fn spring(toast: &str) -> Breakfast {
+        Breakfast::with_fruit(toast, "strawberries")
+    }
+
+    pub fn summer(toast: &str) -> Breakfast {
+        Breakfast::with_fruit(toast, "peaches")
+    }
+
+    pub fn autumn(toast: &str) -> Breakfast {
+        Breakfast::with_fruit(toast, "apples")
+    }
+
+    pub fn winter(toast: &str) -> Breakfast {
+        Breakfast::with_fruit(toast, "oranges")
+    }
+
+    // Read-only access: callers can see which fruit they got, but still can't
+    // change it
+    pub fn season(&self) -> &str {
+        &self.seasonal_fruit
+    }
 }
 
 // In contrast to the struct case, making an enum public makes all its
 // variants public
+#[derive(Debug, PartialEq)]
 pub enum Appetizer {
     Soup,
     Salad,
 }
+
+impl Appetizer {
+    pub fn price(&self) -> f64 {
+        match self {
+            Appetizer::Soup => 5.0,
+            Appetizer::Salad => 6.5,
+        }
+    }
+
+    pub fn is_vegetarian(&self) -> bool {
+        match self {
+            Appetizer::Soup | Appetizer::Salad => true,
+        }
+    }
+}
+
+pub fn order_total(items: &[Appetizer]) -> f64 {
+    items.iter().map(|item| item.price()).sum()
+}
+
+// Taking the predicate as a generic means callers can filter on anything,
+// such as filter_menu(items, Appetizer::is_vegetarian) or a closure on price
+pub fn filter_menu<F: Fn(&Appetizer) -> bool>(items: Vec<Appetizer>, pred: F) -> Vec<Appetizer> {
+    items.into_iter().filter(|item| pred(item)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_season_has_its_own_fruit() {
+        assert_eq!(Breakfast::spring("Rye").season(), "strawberries");
+        assert_eq!(Breakfast::summer("Rye").season(), "peaches");
+        assert_eq!(Breakfast::autumn("Rye").season(), "apples");
+        assert_eq!(Breakfast::winter("Rye").season(), "oranges");
+    }
+
+    #[test]
+    fn empty_order_is_free() {
+        assert_eq!(order_total(&[]), 0.0);
+    }
+
+    #[test]
+    fn mixed_order_sums_prices() {
+        let items = [Appetizer::Soup, Appetizer::Salad, Appetizer::Soup];
+        assert_eq!(order_total(&items), 16.5);
+    }
+
+    #[test]
+    fn filter_menu_keeps_matching_items() {
+        let menu = vec![Appetizer::Soup, Appetizer::Salad];
+        assert_eq!(
+            filter_menu(menu, Appetizer::is_vegetarian),
+            vec![Appetizer::Soup, Appetizer::Salad]
+        );
+
+        let menu = vec![Appetizer::Soup, Appetizer::Salad, Appetizer::Soup];
+        let cheap = filter_menu(menu, |item| item.price() < 6.0);
+        assert_eq!(cheap, vec![Appetizer::Soup, Appetizer::Soup]);
+    }
+}
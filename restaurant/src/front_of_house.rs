@@ -8,10 +8,73 @@ pub mod hosting {
 
 // hosting and serving are "sibling" modules. Could also move each of these
 // into their own files, with front_of_house then being a directory
-mod serving {
-    fn take_order() {}
+pub mod serving {
+    use std::cell::Cell;
+    use std::sync::atomic::{AtomicU32, Ordering};
 
-    fn serve_order() {}
+    // Shared by every call to place_order so each order gets a unique id
+    static NEXT_ORDER_ID: AtomicU32 = AtomicU32::new(1);
+
+    // Like Breakfast, the fields are private so an order can only be marked
+    // served by going through serve(). served is a Cell so that serve() can
+    // flip it through a shared reference: serving an order doesn't need
+    // exclusive access to it
+    #[derive(Debug)]
+    pub struct Order {
+        id: u32,
+        table: u32,
+        served: Cell<bool>,
+    }
+
+    impl Order {
+        pub fn id(&self) -> u32 {
+            self.id
+        }
+
+        pub fn table(&self) -> u32 {
+            self.table
+        }
+
+        pub fn is_served(&self) -> bool {
+            self.served.get()
+        }
+    }
+
+    pub fn place_order(table: u32) -> Order {
+        Order {
+            id: NEXT_ORDER_ID.fetch_add(1, Ordering::Relaxed),
+            table,
+            served: Cell::new(false),
+        }
+    }
+
+    pub fn serve(order: &Order) {
+        order.served.set(true);
+    }
 
     fn take_payment() {}
 }
+
+#[cfg(test)]
+mod tests {
+    use super::serving::*;
+
+    #[test]
+    fn placed_order_is_not_yet_served() {
+        let order = place_order(4);
+        assert_eq!(order.table(), 4);
+        assert!(!order.is_served());
+    }
+
+    #[test]
+    fn serving_an_order_marks_it_served() {
+        let order = place_order(2);
+        serve(&order);
+        assert!(order.is_served());
+    }
+
+    #[test]
+    fn orders_get_distinct_ids() {
+        assert_ne!(place_order(1).id(), place_order(1).id());
+    }
+}
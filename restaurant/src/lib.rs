@@ -31,6 +31,11 @@ mod back_of_house;
 // internals from its public interface.
 pub use crate::front_of_house::hosting; // absolute
 // use self::front_of_house::hosting; // relative
+pub use crate::front_of_house::serving::{place_order, serve, Order};
+
+// back_of_house stays private, but the menu items and the helpers for
+// ordering them are re-exported at the crate root
+pub use crate::back_of_house::{filter_menu, order_total, Appetizer, Breakfast};
 
 // For data structures, the idiom is to bring them fully into scope. The
 // exception is when we are bringing two items with the same name into the same
@@ -66,6 +71,27 @@ pub fn eat_at_restaurant() {
     meal.toast = String::from("Whole wheat"); // can modify pub toast field
     // meal.seasonal_fruit = String::from("strawberries"); // can't modify
 
+    let bill = order_total(&[Appetizer::Soup, Appetizer::Salad]);
+    println!(
+        "{} toast with {} and appetizers come to ${:.2}",
+        meal.toast,
+        meal.season(),
+        bill
+    );
+    let vegetarian = filter_menu(vec![Appetizer::Soup, Appetizer::Salad], |item| {
+        item.is_vegetarian()
+    });
+    println!("Vegetarian appetizers: {:?}", vegetarian);
+
+    let order = place_order(7);
+    serve(&order);
+    println!(
+        "Order {} for table {} served: {}",
+        order.id(),
+        order.table(),
+        order.is_served()
+    );
+
     // This is getting further and further away from a restaurant function...
     let mut map = HashMap::new();
     map.insert(1, 2);
@@ -25,8 +25,100 @@ enum List {
 
 use crate::List::{Cons, Nil};
 
+// Borrowing iteration: ListIter holds a reference to the node it's at, and
+// each call to next copies out that node's value and moves on to its tail
+struct ListIter<'a>(&'a List);
+
+impl Iterator for ListIter<'_> {
+    type Item = i32;
+
+    fn next(&mut self) -> Option<i32> {
+        match self.0 {
+            Cons(value, rest) => {
+                self.0 = rest;
+                Some(*value)
+            }
+            Nil => None,
+        }
+    }
+}
+
+impl List {
+    fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    // Consing onto the front is cheap: the existing list just gets boxed up
+    // as the new node's tail. It takes self by value since the new node owns
+    // the old list
+    fn push_front(self, value: i32) -> List {
+        Cons(value, Box::new(self))
+    }
+
+    fn iter(&self) -> ListIter<'_> {
+        ListIter(self)
+    }
+}
+
+// Consuming a list: into_iter takes the list by value, and each call to next
+// unboxes the head node, keeps its value and moves the rest of the list into
+// the iterator. mem::replace swaps Nil in while we take the current list apart,
+// since we can't move out of a field we only have a &mut to
+struct ListIntoIter(List);
+
+impl Iterator for ListIntoIter {
+    type Item = i32;
+
+    fn next(&mut self) -> Option<i32> {
+        match std::mem::replace(&mut self.0, Nil) {
+            Cons(value, rest) => {
+                self.0 = *rest; // move the tail out of its Box
+                Some(value)
+            }
+            Nil => None,
+        }
+    }
+}
+
+// Implementing IntoIterator (rather than writing an into_iter method by hand)
+// is also what makes `for x in list` work
+impl IntoIterator for List {
+    type Item = i32;
+    type IntoIter = ListIntoIter;
+
+    fn into_iter(self) -> ListIntoIter {
+        ListIntoIter(self)
+    }
+}
+
+// Displays the list like a Vec, e.g. [1, 2, 3], by following the boxed tails
+// until we reach Nil
+use std::fmt;
+
+impl fmt::Display for List {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[")?;
+        let mut node = self;
+        let mut first = true;
+        while let Cons(value, rest) = node {
+            if !first {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", value)?;
+            first = false;
+            node = rest; // &Box<List> derefs to &List
+        }
+        write!(f, "]")
+    }
+}
+
 fn learning_about_box() {
-    let _list = Cons(1, Box::new(Cons(2, Box::new(Cons(3, Box::new(Nil))))));
+    let list = Cons(1, Box::new(Cons(2, Box::new(Cons(3, Box::new(Nil))))));
+    let list = list.push_front(0);
+    println!("Cons list: {} ({} values)", list, list.len());
+    println!("Sum of values: {}", list.iter().sum::<i32>());
+    let values: Vec<i32> = list.into_iter().collect();
+    println!("Cons list values: {:?}", values);
 }
 
 // Learning about the Deref trait: MyBox<T>
@@ -53,6 +145,18 @@ impl<T> Deref for MyBox<T> {
     }
 }
 
+// DerefMut is the mutable counterpart of Deref: where deref turns &MyBox<T>
+// into &T, deref_mut turns &mut MyBox<T> into &mut T, which is what lets us
+// assign through the box with *b = x. It reuses Deref's Target, so there's no
+// associated type to declare here
+use std::ops::DerefMut;
+
+impl<T> DerefMut for MyBox<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
 fn learning_about_mybox() {
     let x = 5;
     let y = &x; // ref pointing to value of x
@@ -62,6 +166,56 @@ fn learning_about_mybox() {
     assert_eq!(5, *y);
     assert_eq!(5, *z);
     assert_eq!(5, *w); // *(w.deref())
+    let mut v = MyBox::new(x);
+    *v += 1; // *(v.deref_mut()) += 1
+    assert_eq!(6, *v);
+}
+
+// MyBox keeps its value inline, but a real Box<T> owns a heap allocation.
+// HeapBox<T> does that too: Box::into_raw gives up the Box's ownership and
+// hands back a raw pointer to the heap value, and Box::from_raw turns it back
+// into a Box so the value can be dropped and the memory freed. The unsafe
+// blocks are sound because HeapBox is the only owner of the pointer, it's never
+// null, and it's freed exactly once (in drop), so users of HeapBox never have
+// to write unsafe themselves
+struct HeapBox<T> {
+    ptr: *mut T,
+}
+
+impl<T> HeapBox<T> {
+    fn new(x: T) -> HeapBox<T> {
+        HeapBox {
+            ptr: Box::into_raw(Box::new(x)),
+        }
+    }
+}
+
+impl<T> Deref for HeapBox<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.ptr }
+    }
+}
+
+impl<T> DerefMut for HeapBox<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.ptr }
+    }
+}
+
+impl<T> Drop for HeapBox<T> {
+    fn drop(&mut self) {
+        // the Box we rebuild here drops the value and frees the allocation
+        // when it goes out of scope at the end of this statement
+        unsafe { drop(Box::from_raw(self.ptr)) };
+    }
+}
+
+fn learning_about_heapbox() {
+    let mut greeting = HeapBox::new(String::from("Hello"));
+    greeting.push_str(", heap!"); // auto-deref through DerefMut
+    tst(&greeting); // &HeapBox<String> --> &String --> &str
 }
 
 // On a related note, deref coercion is a convenience that Rust performs on
@@ -175,6 +329,40 @@ pub trait Messenger {
     fn send(&self, msg: &str); // immutable reference to self
 }
 
+// LimitTracker keeps an eye on how close value is to max and uses a
+// Messenger to send a warning when a threshold is crossed. It only holds a
+// reference to the messenger, so it needs a lifetime parameter: the
+// LimitTracker can't outlive the messenger it borrows
+pub struct LimitTracker<'a, M: Messenger> {
+    messenger: &'a M,
+    value: usize,
+    max: usize,
+}
+
+impl<'a, M: Messenger> LimitTracker<'a, M> {
+    pub fn new(messenger: &'a M, max: usize) -> LimitTracker<'a, M> {
+        LimitTracker {
+            messenger,
+            value: 0,
+            max,
+        }
+    }
+
+    pub fn set_value(&mut self, value: usize) {
+        self.value = value;
+        let percentage_of_max = self.value as f64 / self.max as f64;
+        if percentage_of_max >= 1.0 {
+            self.messenger.send("Error: You are over your quota!");
+        } else if percentage_of_max >= 0.9 {
+            self.messenger
+                .send("Urgent warning: You've used up over 90% of your quota!");
+        } else if percentage_of_max >= 0.75 {
+            self.messenger
+                .send("Warning: You've used up over 75% of your quota!");
+        }
+    }
+}
+
 use std::cell::RefCell;
 
 pub struct MockMessenger{
@@ -187,6 +375,16 @@ impl MockMessenger {
             sent_messages: RefCell::new(vec![])
         }
     }
+
+    // Accessors so callers don't have to know the messages live in a RefCell.
+    // Each borrow() only lasts until the end of its method
+    fn sent_count(&self) -> usize {
+        self.sent_messages.borrow().len()
+    }
+
+    fn last_message(&self) -> Option<String> {
+        self.sent_messages.borrow().last().cloned()
+    }
 }
 
 impl Messenger for MockMessenger {
@@ -201,7 +399,79 @@ impl Messenger for MockMessenger {
 fn learning_about_refcell() {
     let messenger = MockMessenger::new(); // immutable value
     messenger.send("Hello world!"); // mutates internal state
-    assert_eq!(messenger.sent_messages.borrow().len(), 1);
+    assert_eq!(messenger.sent_count(), 1);
+
+    // LimitTracker only has a shared reference to the messenger, which is
+    // exactly the situation RefCell lets MockMessenger cope with
+    let mut tracker = LimitTracker::new(&messenger, 100);
+    tracker.set_value(80);
+    println!(
+        "{} messages sent, the last one was {:?}",
+        messenger.sent_count(),
+        messenger.last_message()
+    );
+}
+
+// Combining Rc<T> and RefCell<T> gets us data with multiple owners that any of
+// them can mutate: Rc shares ownership of the RefCell, and the RefCell lets
+// each owner borrow the Vec mutably even though an Rc only hands out shared
+// references. Cloning a Stack clones the Rc, not the Vec, so every clone is a
+// handle to the same stack
+struct Stack<T> {
+    items: Rc<RefCell<Vec<T>>>,
+}
+
+// #[derive(Clone)] would insist on T: Clone, but cloning the handle never
+// clones any items
+impl<T> Clone for Stack<T> {
+    fn clone(&self) -> Stack<T> {
+        Stack {
+            items: Rc::clone(&self.items),
+        }
+    }
+}
+
+impl<T> Stack<T> {
+    fn new() -> Stack<T> {
+        Stack {
+            items: Rc::new(RefCell::new(Vec::new())),
+        }
+    }
+
+    fn push(&self, item: T) {
+        self.items.borrow_mut().push(item);
+    }
+
+    fn pop(&self) -> Option<T> {
+        self.items.borrow_mut().pop()
+    }
+
+    fn len(&self) -> usize {
+        self.items.borrow().len()
+    }
+}
+
+// peek can't return a reference to the top item, since that reference would
+// outlive the Ref that borrow() hands back, so it returns a copy instead
+impl<T: Clone> Stack<T> {
+    fn peek(&self) -> Option<T> {
+        self.items.borrow().last().cloned()
+    }
+}
+
+fn learning_about_shared_stack() {
+    let undo = Stack::new();
+    let editor_view = undo.clone();
+    undo.push("typed a word");
+    editor_view.push("deleted a line");
+    println!(
+        "{} edits, the latest is {:?}",
+        editor_view.len(),
+        undo.peek()
+    );
+    while let Some(edit) = editor_view.pop() {
+        println!("Undoing: {}", edit);
+    }
 }
 
 // Rust's memory safety makes it difficult, although not impossible, for memory
@@ -236,6 +506,70 @@ struct Node {
     children: RefCell<Vec<Rc<Node>>>,
 }
 
+impl Node {
+    // Number of nodes on the longest path from this node down to a leaf, so a
+    // leaf has depth 1. borrow() hands out a Ref to the children for as long
+    // as we're looking at them
+    fn depth(&self) -> usize {
+        1 + self
+            .children
+            .borrow()
+            .iter()
+            .map(|child| child.depth())
+            .max()
+            .unwrap_or(0)
+    }
+
+    // Follows parent links up to the topmost node still alive. Upgrading a
+    // Weak gives None both for "no parent" and for "the parent has already
+    // been dropped", so either way that's as far up as we can go. Taking
+    // self: &Rc<Node> lets us hand back an Rc to self when it's the root
+    fn root(self: &Rc<Node>) -> Rc<Node> {
+        let mut current = Rc::clone(self);
+        loop {
+            let parent = current.parent.borrow().upgrade();
+            match parent {
+                Some(parent) => current = parent,
+                None => return current,
+            }
+        }
+    }
+
+    // Number of nodes below this one (children, grandchildren, ...), not
+    // counting the node itself
+    fn count_descendants(&self) -> usize {
+        self.children
+            .borrow()
+            .iter()
+            .map(|child| 1 + child.count_descendants())
+            .sum()
+    }
+}
+
+// Walks the tree in pre-order (a node before its children, children left to
+// right) and keeps f(value) for every node where f returns Some. F is Copy so
+// each recursive call can take its own copy of the closure
+fn filter_map_values<T, F: Fn(i32) -> Option<T> + Copy>(root: &Rc<Node>, f: F) -> Vec<T> {
+    let mut values: Vec<T> = f(root.value).into_iter().collect();
+    for child in root.children.borrow().iter() {
+        values.extend(filter_map_values(child, f));
+    }
+    values
+}
+
+// Depth-first search for the first node holding target, checking a node
+// before its children. The match is handed back as another Rc pointing at the
+// same node, so the caller shares ownership rather than getting a copy
+fn find(root: &Rc<Node>, target: i32) -> Option<Rc<Node>> {
+    if root.value == target {
+        return Some(Rc::clone(root));
+    }
+    root.children
+        .borrow()
+        .iter()
+        .find_map(|child| find(child, target))
+}
+
 fn learning_about_ref_cycles() {
     let leaf = Rc::new(Node {
         value: 2,
@@ -253,14 +587,230 @@ fn learning_about_ref_cycles() {
     // lack of infinite output of the below is an indication that this code is
     // free of reference cycles
     println!("leaf parent is {:?}", leaf.parent.borrow().upgrade());
+    println!(
+        "branch has depth {} and {} descendant(s)",
+        branch.depth(),
+        branch.count_descendants()
+    );
+    println!("leaf's root has value {}", leaf.root().value);
+    if let Some(node) = find(&branch, 2) {
+        println!("found a node with value {} under branch", node.value);
+    }
+    let odd = filter_map_values(&branch, |v| if v % 2 == 1 { Some(v) } else { None });
+    println!("odd values in the tree: {:?}", odd);
 }
 
 fn main() {
     learning_about_box();
     learning_about_mybox();
     learning_about_deref_coercion();
+    learning_about_heapbox();
     learning_about_drop();
     learning_about_rc();
     learning_about_refcell();
+    learning_about_shared_stack();
     learning_about_ref_cycles();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn list_into_iter_yields_owned_values() {
+        let list = Cons(1, Box::new(Cons(2, Box::new(Cons(3, Box::new(Nil))))));
+        let values: Vec<i32> = list.into_iter().collect();
+        // list has been moved into the iterator, so using it here would be a
+        // compile error ("borrow of moved value")
+        assert_eq!(values, vec![1, 2, 3]);
+        assert_eq!(Nil.into_iter().next(), None);
+    }
+
+    // Builds a node that owns children and points each child back at it
+    fn tree_node(value: i32, children: Vec<Rc<Node>>) -> Rc<Node> {
+        let node = Rc::new(Node {
+            value,
+            parent: RefCell::new(Weak::new()),
+            children: RefCell::new(children),
+        });
+        for child in node.children.borrow().iter() {
+            *child.parent.borrow_mut() = Rc::downgrade(&node);
+        }
+        node
+    }
+
+    #[test]
+    fn filter_map_values_in_pre_order() {
+        //        1
+        //      / | \
+        //     2  3  4
+        //    / \     \
+        //   6   7     8
+        let root = tree_node(
+            1,
+            vec![
+                tree_node(2, vec![tree_node(6, vec![]), tree_node(7, vec![])]),
+                tree_node(3, vec![]),
+                tree_node(4, vec![tree_node(8, vec![])]),
+            ],
+        );
+        let doubled_evens =
+            filter_map_values(&root, |v| if v % 2 == 0 { Some(v * 2) } else { None });
+        assert_eq!(doubled_evens, vec![4, 12, 8, 16]);
+    }
+
+    #[test]
+    fn mybox_deref_mut() {
+        let mut b = MyBox::new(5);
+        *b = 10;
+        assert_eq!(b.0, 10);
+        assert_eq!(*b, 10);
+    }
+
+    #[test]
+    fn heapbox_derefs_to_its_value() {
+        let mut b = HeapBox::new(String::from("boxed"));
+        assert_eq!(*b, "boxed");
+        assert_eq!(b.len(), 5);
+        b.push_str(" up");
+        assert_eq!(*b, "boxed up");
+    }
+
+    #[test]
+    fn heapbox_drops_its_value_once() {
+        use std::cell::Cell;
+
+        struct DropCounter<'a>(&'a Cell<usize>);
+
+        impl Drop for DropCounter<'_> {
+            fn drop(&mut self) {
+                self.0.set(self.0.get() + 1);
+            }
+        }
+
+        let drops = Cell::new(0);
+        let b = HeapBox::new(DropCounter(&drops));
+        assert_eq!(drops.get(), 0);
+        drop(b);
+        assert_eq!(drops.get(), 1);
+    }
+
+    #[test]
+    fn list_len_push_front_and_iter() {
+        let list = Nil.push_front(3).push_front(2).push_front(1);
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.iter().collect::<Vec<_>>(), vec![1, 2, 3]);
+        // iter only borrows, so the list can still be used afterwards
+        assert_eq!(list.to_string(), "[1, 2, 3]");
+        assert_eq!(Nil.len(), 0);
+    }
+
+    #[test]
+    fn node_depth_and_descendants() {
+        let root = tree_node(
+            1,
+            vec![
+                tree_node(2, vec![tree_node(4, vec![])]),
+                tree_node(3, vec![]),
+            ],
+        );
+        assert_eq!(root.depth(), 3);
+        assert_eq!(root.count_descendants(), 3);
+        let leaf = tree_node(5, vec![]);
+        assert_eq!(leaf.depth(), 1);
+        assert_eq!(leaf.count_descendants(), 0);
+    }
+
+    #[test]
+    fn find_searches_the_whole_tree() {
+        let root = tree_node(
+            1,
+            vec![
+                tree_node(2, vec![tree_node(5, vec![tree_node(9, vec![])])]),
+                tree_node(3, vec![]),
+            ],
+        );
+        let found = find(&root, 9).unwrap();
+        assert_eq!(found.value, 9);
+        assert_eq!(found.parent.borrow().upgrade().unwrap().value, 5);
+        assert_eq!(find(&root, 1).unwrap().value, 1);
+        assert!(find(&root, 42).is_none());
+    }
+
+    #[test]
+    fn root_walks_up_parent_links() {
+        let branch = tree_node(4, vec![tree_node(2, vec![])]);
+        let leaf = Rc::clone(&branch.children.borrow()[0]);
+        assert!(Rc::ptr_eq(&leaf.root(), &branch));
+        assert!(Rc::ptr_eq(&branch.root(), &branch));
+
+        // once the top of the tree is dropped, the walk stops at the highest
+        // node that's still alive
+        let top = tree_node(1, vec![tree_node(2, vec![tree_node(3, vec![])])]);
+        let middle = Rc::clone(&top.children.borrow()[0]);
+        let bottom = Rc::clone(&middle.children.borrow()[0]);
+        drop(top);
+        assert!(Rc::ptr_eq(&bottom.root(), &middle));
+    }
+
+    #[test]
+    fn mock_messenger_accessors() {
+        let messenger = MockMessenger::new();
+        assert_eq!(messenger.sent_count(), 0);
+        assert_eq!(messenger.last_message(), None);
+        messenger.send("first");
+        messenger.send("second");
+        assert_eq!(messenger.sent_count(), 2);
+        assert_eq!(messenger.last_message(), Some(String::from("second")));
+    }
+
+    #[test]
+    fn limit_tracker_sends_a_message_per_threshold() {
+        let messenger = MockMessenger::new();
+        let mut tracker = LimitTracker::new(&messenger, 100);
+        tracker.set_value(50);
+        assert_eq!(messenger.sent_count(), 0);
+        assert_eq!(messenger.last_message(), None);
+        tracker.set_value(75);
+        assert_eq!(messenger.sent_count(), 1);
+        assert_eq!(
+            messenger.last_message().as_deref(),
+            Some("Warning: You've used up over 75% of your quota!")
+        );
+        tracker.set_value(95);
+        assert_eq!(messenger.sent_count(), 2);
+        assert_eq!(
+            messenger.last_message().as_deref(),
+            Some("Urgent warning: You've used up over 90% of your quota!")
+        );
+        tracker.set_value(101);
+        assert_eq!(messenger.sent_count(), 3);
+        assert_eq!(
+            messenger.last_message().as_deref(),
+            Some("Error: You are over your quota!")
+        );
+    }
+
+    #[test]
+    fn stack_clones_share_data() {
+        let a = Stack::new();
+        let b = a.clone();
+        a.push(1);
+        assert_eq!(b.len(), 1);
+        assert_eq!(b.peek(), Some(1));
+        b.push(2);
+        assert_eq!(a.peek(), Some(2));
+        assert_eq!(a.pop(), Some(2));
+        assert_eq!(b.pop(), Some(1));
+        assert_eq!(a.pop(), None);
+        assert_eq!(b.len(), 0);
+    }
+
+    #[test]
+    fn list_display() {
+        let list = Cons(1, Box::new(Cons(2, Box::new(Cons(3, Box::new(Nil))))));
+        assert_eq!(list.to_string(), "[1, 2, 3]");
+        assert_eq!(Cons(7, Box::new(Nil)).to_string(), "[7]");
+        assert_eq!(Nil.to_string(), "[]");
+    }
+}
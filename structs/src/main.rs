@@ -1,3 +1,5 @@
+use std::cmp::Ordering;
+
 // structs are used to keep associated pieces of data connected with each other
 #[derive(Debug)]
 struct User {
@@ -10,13 +12,38 @@ struct User {
     sign_in_count: u64,
 }
 
-fn build_user(email: String, username: String) -> User {
-    User {
-        email,
-        username,
-        active: true,
-        sign_in_count: 1,
+impl User {
+    // The checks are deliberately basic: an email just needs an '@' somewhere
+    fn new(email: String, username: String) -> Result<User, String> {
+        if !email.contains('@') {
+            return Err(format!("Invalid email '{}': it has no '@'", email));
+        }
+        if username.is_empty() {
+            return Err(String::from("Username can't be empty"));
+        }
+        Ok(User {
+            email, // field init shorthand, same as email: email
+            username,
+            active: true,
+            sign_in_count: 1,
+        })
+    }
+
+    // methods that change the struct's state need &mut self, so they can
+    // only be called on a mutable binding
+    fn record_sign_in(&mut self) {
+        self.sign_in_count += 1;
+        self.active = true;
     }
+
+    fn deactivate(&mut self) {
+        self.active = false;
+    }
+}
+
+// Kept for the demo below, where the input is known to be valid
+fn build_user(email: String, username: String) -> User {
+    User::new(email, username).unwrap_or_else(|err| panic!("{}", err))
 }
 
 #[derive(Debug)]
@@ -37,6 +64,19 @@ impl Rectangle {
         self.width > other.width && self.height > other.height
     }
 
+    fn perimeter(&self) -> u32 {
+        2 * (self.width + self.height)
+    }
+
+    // Returns a new, resized rectangle and leaves self as it was, which is why
+    // &self is enough here
+    fn scale(&self, factor: u32) -> Rectangle {
+        Rectangle {
+            width: self.width * factor,
+            height: self.height * factor,
+        }
+    }
+
     // associated functions don't need to have self as the first parameter if
     // they're not intended to be class methods. Can use such a technique to
     // define (effectively) static class methods
@@ -46,6 +86,35 @@ impl Rectangle {
             height: size,
         }
     }
+
+    fn from_tuple(dims: (u32, u32)) -> Rectangle {
+        Rectangle::from(dims)
+    }
+}
+
+// Rectangles compare by size: equal areas are equal (so a 2x3 and a 3x2, or
+// even a 1x6, are all "the same"), and a bigger area is greater. Defining
+// both traits in terms of area() keeps them consistent with each other: a ==
+// b exactly when partial_cmp says Equal. Comparing u32s always gives an
+// answer, so partial_cmp never returns None
+impl PartialEq for Rectangle {
+    fn eq(&self, other: &Rectangle) -> bool {
+        self.area() == other.area()
+    }
+}
+
+impl PartialOrd for Rectangle {
+    fn partial_cmp(&self, other: &Rectangle) -> Option<Ordering> {
+        self.area().partial_cmp(&other.area())
+    }
+}
+
+// Implementing From gives us Rectangle::from((w, h)) and, for free, the
+// matching Into: (w, h).into() works wherever a Rectangle is expected
+impl From<(u32, u32)> for Rectangle {
+    fn from((width, height): (u32, u32)) -> Rectangle {
+        Rectangle { width, height }
+    }
 }
 
 // 1. The code below is BAD. The width and height parameters are related (same
@@ -91,11 +160,13 @@ fn main() {
         // struct update syntax. In this case, the reference to user1 is
         // invalidated by the update, since ownership of the username string is
         // moved
-        let user2 = User {
+        let mut user2 = User {
             email: String::from("example2@mail.com"),
             username: String::from("user2"),
             ..user1 // fields not explicitly set should have same value as user1
         };
+        user2.deactivate();
+        user2.record_sign_in();
         println!("user1: {:#?}\nuser2: {:#?}", user1, user2);
     }
 
@@ -126,6 +197,98 @@ fn main() {
     dbg!(&rect1);
     println!("Area is {}", rect1.area());
     println!("rect1 can hold rect2 is {}", rect1.can_hold(&rect2));
+    println!("Perimeter is {}", rect1.perimeter());
+    println!("rect1 scaled by 3 is {:?}", rect1.scale(3));
     let sq1 = Rectangle::square(1);
     println!("sq1 is {:#?}", sq1);
+    println!("rect1 is bigger than sq1 is {}", rect1 > sq1);
+    let wide: Rectangle = (4, 1).into();
+    println!(
+        "wide is {:?}, tall is {:?}",
+        wide,
+        Rectangle::from_tuple((1, 4))
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn perimeter_of_2_by_3() {
+        let rect = Rectangle {
+            width: 2,
+            height: 3,
+        };
+        assert_eq!(rect.perimeter(), 10);
+    }
+
+    #[test]
+    fn from_tuple_builds_rectangle() {
+        assert_eq!(Rectangle::from((5, 5)), Rectangle::square(5));
+        // PartialEq only compares areas, so check the dimensions themselves
+        let square = Rectangle::from((5, 5));
+        assert_eq!((square.width, square.height), (5, 5));
+        let rect: Rectangle = (2, 3).into();
+        assert_eq!((rect.width, rect.height), (2, 3));
+        let rect = Rectangle::from_tuple((3, 2));
+        assert_eq!((rect.width, rect.height), (3, 2));
+    }
+
+    #[test]
+    fn rectangles_sort_by_area() {
+        let mut rects: Vec<Rectangle> = vec![(3, 3).into(), (1, 2).into(), (4, 1).into()];
+        rects.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let areas: Vec<u32> = rects.iter().map(|r| r.area()).collect();
+        assert_eq!(areas, vec![2, 4, 9]);
+        assert_eq!(Rectangle::from((2, 3)), Rectangle::from((1, 6)));
+        assert!(Rectangle::square(3) > Rectangle::from((2, 4)));
+    }
+
+    #[test]
+    fn scale_multiplies_both_dimensions() {
+        let rect = Rectangle {
+            width: 2,
+            height: 3,
+        };
+        let scaled = rect.scale(2);
+        assert_eq!((scaled.width, scaled.height), (4, 6));
+        assert_eq!((rect.width, rect.height), (2, 3)); // original unchanged
+    }
+
+    #[test]
+    fn valid_user() {
+        let user = User::new(String::from("a@b.com"), String::from("ab")).unwrap();
+        assert_eq!(user.email, "a@b.com");
+        assert_eq!(user.username, "ab");
+        assert!(user.active);
+        assert_eq!(user.sign_in_count, 1);
+    }
+
+    #[test]
+    fn email_without_at_is_rejected() {
+        assert_eq!(
+            User::new(String::from("ab.com"), String::from("ab")).unwrap_err(),
+            "Invalid email 'ab.com': it has no '@'"
+        );
+    }
+
+    #[test]
+    fn empty_username_is_rejected() {
+        assert_eq!(
+            User::new(String::from("a@b.com"), String::new()).unwrap_err(),
+            "Username can't be empty"
+        );
+    }
+
+    #[test]
+    fn record_sign_in_counts_and_activates() {
+        let mut user = build_user(String::from("a@b.com"), String::from("ab"));
+        user.deactivate();
+        assert!(!user.active);
+        user.record_sign_in();
+        user.record_sign_in();
+        assert!(user.active);
+        assert_eq!(user.sign_in_count, 3);
+    }
 }